@@ -5,7 +5,10 @@
 use ark_bls12_381::{Bls12_381, Fr as BlsFr};
 use ark_crypto_primitives::snark::SNARK;
 use ark_ff::{PrimeField, UniformRand};
-use ark_groth16::Groth16;
+use ark_groth16::{
+    msm::{ArkworksMsm, MsmBackend, ThresholdMsm},
+    Groth16,
+};
 use ark_mnt4_298::{Fr as MNT4Fr, MNT4_298};
 use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
 use ark_mnt6_298::{Fr as MNT6Fr, MNT6_298};
@@ -19,6 +22,10 @@ const NUM_PROVE_REPETITIONS: usize = 1;
 const NUM_VERIFY_REPETITIONS: usize = 50;
 const NUM_CONSTRAINTS: usize = (1 << 20) - 100;
 const NUM_VARIABLES: usize = (1 << 20) - 100;
+const NUM_SMALL_PROVE_REPETITIONS: usize = 100;
+const NUM_SMALL_CONSTRAINTS: usize = 16;
+const NUM_SAME_INSTANCE_PROOFS: usize = 1000;
+const NUM_BATCH_PROOFS: usize = 10_000;
 
 #[derive(Copy)]
 struct DummyCircuit<F: PrimeField> {
@@ -142,7 +149,155 @@ fn bench_verify() {
     groth16_verify_bench!(mnt6big, MNT6BigFr, MNT6_753);
 }
 
+fn bench_prove_small_with_msm<M: MsmBackend>(name: &str, msm: &M) {
+    use ark_std::rand::SeedableRng;
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let c = DummyCircuit::<BlsFr> {
+        a: Some(BlsFr::rand(rng)),
+        b: Some(BlsFr::rand(rng)),
+        num_variables: NUM_SMALL_CONSTRAINTS,
+        num_constraints: NUM_SMALL_CONSTRAINTS,
+    };
+
+    let (pk, _) = Groth16::<Bls12_381>::circuit_specific_setup(c, rng).unwrap();
+
+    let start = ark_std::time::Instant::now();
+
+    for _ in 0..NUM_SMALL_PROVE_REPETITIONS {
+        let r = BlsFr::rand(rng);
+        let s = BlsFr::rand(rng);
+        let _ =
+            Groth16::<Bls12_381>::create_proof_with_reduction_and_msm(c, &pk, r, s, msm).unwrap();
+    }
+
+    println!(
+        "small-circuit proving time for Bls12_381 with {}: {} ns",
+        name,
+        start.elapsed().as_nanos() / NUM_SMALL_PROVE_REPETITIONS as u128
+    );
+}
+
+fn bench_prove_small() {
+    bench_prove_small_with_msm("ArkworksMsm", &ArkworksMsm);
+    bench_prove_small_with_msm("ThresholdMsm", &ThresholdMsm::default());
+}
+
+fn bench_verify_same_instance() {
+    use ark_ec::CurveGroup;
+    use ark_groth16::prepare_verifying_key;
+    use ark_std::rand::SeedableRng;
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let c = DummyCircuit::<BlsFr> {
+        a: Some(BlsFr::rand(rng)),
+        b: Some(BlsFr::rand(rng)),
+        num_variables: NUM_SMALL_CONSTRAINTS,
+        num_constraints: NUM_SMALL_CONSTRAINTS,
+    };
+
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(c, rng).unwrap();
+    let pvk = prepare_verifying_key(&vk).unwrap();
+    let proof = Groth16::<Bls12_381>::prove(&pk, c, rng).unwrap();
+    let prepared_inputs =
+        Groth16::<Bls12_381>::prepare_inputs(&pvk, &[c.a.unwrap() * c.b.unwrap()]).unwrap();
+
+    let start = ark_std::time::Instant::now();
+    for _ in 0..NUM_SAME_INSTANCE_PROOFS {
+        let _ =
+            Groth16::<Bls12_381>::verify_proof_with_prepared_inputs(&pvk, &proof, &prepared_inputs)
+                .unwrap();
+    }
+    println!(
+        "verifying time for {} proofs of one instance with projective inputs: {} ms",
+        NUM_SAME_INSTANCE_PROOFS,
+        start.elapsed().as_millis()
+    );
+
+    let start = ark_std::time::Instant::now();
+    let prepared_inputs = prepared_inputs.into_affine();
+    for _ in 0..NUM_SAME_INSTANCE_PROOFS {
+        let _ = Groth16::<Bls12_381>::verify_proof_with_prepared_affine_inputs(
+            &pvk,
+            &proof,
+            &prepared_inputs,
+        )
+        .unwrap();
+    }
+    println!(
+        "verifying time for {} proofs of one instance with affine inputs: {} ms",
+        NUM_SAME_INSTANCE_PROOFS,
+        start.elapsed().as_millis()
+    );
+}
+
+fn bench_batch_verify() {
+    use ark_groth16::prepare_verifying_key;
+    use ark_std::rand::SeedableRng;
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let c = DummyCircuit::<BlsFr> {
+        a: Some(BlsFr::rand(rng)),
+        b: Some(BlsFr::rand(rng)),
+        num_variables: NUM_SMALL_CONSTRAINTS,
+        num_constraints: NUM_SMALL_CONSTRAINTS,
+    };
+
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(c, rng).unwrap();
+    let pvk = prepare_verifying_key(&vk).unwrap();
+    let proof = Groth16::<Bls12_381>::prove(&pk, c, rng).unwrap();
+    let proofs = vec![proof; NUM_BATCH_PROOFS];
+    let public_inputs = vec![vec![c.a.unwrap() * c.b.unwrap()]; NUM_BATCH_PROOFS];
+
+    let run = || {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(1u64);
+        let start = ark_std::time::Instant::now();
+        assert!(
+            Groth16::<Bls12_381>::batch_verify_proofs(&pvk, &proofs, &public_inputs, rng).unwrap()
+        );
+        start.elapsed().as_millis()
+    };
+
+    println!(
+        "batch verifying time for {} proofs: {} ms",
+        NUM_BATCH_PROOFS,
+        run()
+    );
+
+    #[cfg(feature = "parallel")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        println!(
+            "batch verifying time for {} proofs on a single thread: {} ms",
+            NUM_BATCH_PROOFS,
+            pool.install(run)
+        );
+    }
+}
+
+fn bench_setup() {
+    use ark_std::rand::SeedableRng;
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let c = DummyCircuit::<BlsFr> {
+        a: None,
+        b: None,
+        num_variables: NUM_VARIABLES,
+        num_constraints: NUM_CONSTRAINTS,
+    };
+
+    let start = ark_std::time::Instant::now();
+    let _ = Groth16::<Bls12_381>::circuit_specific_setup(c, rng).unwrap();
+    println!(
+        "setup time for Bls12_381: {} s",
+        start.elapsed().as_secs_f64()
+    );
+}
+
 fn main() {
+    bench_prove_small();
+    bench_verify_same_instance();
+    bench_batch_verify();
+    bench_setup();
     bench_prove();
     bench_verify();
 }
@@ -0,0 +1,59 @@
+use crate::{prover::uncancelled, Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult};
+use ark_std::{ops::Neg, rand::Rng};
+
+/// Generate a random common reference string for `circuit`, as
+/// [`Groth16::generate_random_parameters_with_reduction`] does, with its
+/// [`SetupError`](crate::generator::SetupError) flattened into a
+/// [`SynthesisError`](ark_relations::r1cs::SynthesisError).
+pub fn generate_random_parameters<E, C, R>(circuit: C, rng: &mut R) -> R1CSResult<ProvingKey<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+{
+    Ok(Groth16::<E>::generate_random_parameters_with_reduction(
+        circuit, rng,
+    )?)
+}
+
+/// Create a zero-knowledge proof for `circuit`, as
+/// [`Groth16::create_random_proof_with_reduction`] does, with its
+/// [`ProvingError`](crate::prover::ProvingError) flattened into a
+/// [`SynthesisError`](ark_relations::r1cs::SynthesisError).
+pub fn create_random_proof<E, C, R>(
+    circuit: C,
+    pk: &ProvingKey<E>,
+    rng: &mut R,
+) -> R1CSResult<Proof<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+{
+    uncancelled(Groth16::<E>::create_random_proof_with_reduction(
+        circuit, pk, rng,
+    ))
+}
+
+/// Prepare `vk` for verification. Unlike [`crate::prepare_verifying_key`], this
+/// does not reject keys whose generators are the identity.
+pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
+    PreparedVerifyingKey {
+        vk: vk.clone(),
+        alpha_g1_beta_g2: E::pairing(vk.alpha_g1, vk.beta_g2).0,
+        gamma_g2_neg_pc: vk.gamma_g2.into_group().neg().into_affine().into(),
+        delta_g2_neg_pc: vk.delta_g2.into_group().neg().into_affine().into(),
+    }
+}
+
+/// Verify `proof` against `pvk` and `public_inputs`, as
+/// [`Groth16::verify_proof`] does.
+pub fn verify_proof<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    public_inputs: &[E::ScalarField],
+) -> R1CSResult<bool> {
+    Groth16::<E>::verify_proof(pvk, proof, public_inputs)
+}
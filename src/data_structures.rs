@@ -1,8 +1,10 @@
 use ark_crypto_primitives::sponge::Absorb;
-use ark_ec::pairing::Pairing;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::PrimeField;
+use ark_relations::r1cs::{OptimizationGoal, Result as R1CSResult, SynthesisError};
 use ark_serialize::*;
-use ark_std::vec::Vec;
+use ark_std::{vec, vec::Vec};
+use sha2::{Digest, Sha256};
 
 /// A proof in the Groth16 SNARK.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -25,6 +27,46 @@ impl<E: Pairing> Default for Proof<E> {
     }
 }
 
+impl<E: Pairing> Proof<E> {
+    /// Serialize the proof in network byte order: each element is encoded in
+    /// compressed form, with the bytes of its canonical (little-endian)
+    /// encoding reversed, so that field elements appear big-endian. This is
+    /// not the format expected by the EVM precompiles, which take
+    /// uncompressed points.
+    pub fn serialize_network<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        serialize_reversed(&self.a, &mut writer)?;
+        serialize_reversed(&self.b, &mut writer)?;
+        serialize_reversed(&self.c, &mut writer)
+    }
+
+    /// Deserialize a proof written by [`Self::serialize_network`].
+    pub fn deserialize_network<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(Self {
+            a: deserialize_reversed(&mut reader)?,
+            b: deserialize_reversed(&mut reader)?,
+            c: deserialize_reversed(&mut reader)?,
+        })
+    }
+}
+
+fn serialize_reversed<G: AffineRepr, W: Write>(
+    point: &G,
+    mut writer: W,
+) -> Result<(), SerializationError> {
+    let mut bytes = Vec::with_capacity(point.compressed_size());
+    point.serialize_compressed(&mut bytes)?;
+    bytes.reverse();
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+fn deserialize_reversed<G: AffineRepr, R: Read>(mut reader: R) -> Result<G, SerializationError> {
+    let mut bytes = vec![0u8; G::zero().compressed_size()];
+    reader.read_exact(&mut bytes)?;
+    bytes.reverse();
+    G::deserialize_compressed(&bytes[..])
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// A verification key in the Groth16 SNARK.
@@ -82,6 +124,141 @@ where
     }
 }
 
+/// A summary of the shape of a [`VerifyingKey`].
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKeyDescription {
+    /// The number of instance variables, including the constant one.
+    pub num_instance_variables: u64,
+    /// The compressed size in bytes of an element of `E::G1`.
+    pub g1_size: u64,
+    /// The compressed size in bytes of an element of `E::G2`.
+    pub g2_size: u64,
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Describe the shape of the verification key.
+    pub fn describe(&self) -> VerifyingKeyDescription {
+        VerifyingKeyDescription {
+            num_instance_variables: self.gamma_abc_g1.len() as u64,
+            g1_size: E::G1Affine::generator().compressed_size() as u64,
+            g2_size: E::G2Affine::generator().compressed_size() as u64,
+        }
+    }
+
+    /// The element `gamma_abc_g1[0]`, which is the coefficient of the constant one
+    /// instance variable and is always added to the prepared inputs.
+    ///
+    /// Returns [`SynthesisError::MalformedVerifyingKey`] if `self.gamma_abc_g1` is
+    /// empty, which is never the case for keys produced by the generator.
+    pub fn constant_term(&self) -> R1CSResult<E::G1Affine> {
+        self.gamma_abc_g1
+            .first()
+            .copied()
+            .ok_or(SynthesisError::MalformedVerifyingKey)
+    }
+
+    /// Compute a SHA-256 fingerprint over the compressed canonical serialization
+    /// of the key, so that byte-identical keys share a fingerprint. This is meant
+    /// for identifying keys in logs and configuration, not as a security check.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes)
+            .expect("serializing to a vector cannot fail");
+        Sha256::digest(&bytes).into()
+    }
+
+    /// Split a full assignment, laid out as the instance variables (starting with
+    /// the constant `1`) followed by the witness variables, into its instance and
+    /// witness parts according to the number of instance variables of this key.
+    ///
+    /// Panics if `full` is shorter than `self.gamma_abc_g1.len()`.
+    pub fn partition_assignment<'a>(
+        &self,
+        full: &'a [E::ScalarField],
+    ) -> (&'a [E::ScalarField], &'a [E::ScalarField]) {
+        full.split_at(self.gamma_abc_g1.len())
+    }
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Check that the proving key `pk` and `self` stem from the same trapdoor, as a
+    /// sanity check after a setup ceremony. This checks that:
+    /// * `pk.vk` equals `self`;
+    /// * `e(beta_g1, delta_g2) == e(delta_g1, beta_g2)`;
+    /// * `e(b_g1_query[i], delta_g2) == e(delta_g1, b_g2_query[i])` for all `i`.
+    ///
+    /// The remaining elements (`alpha_g1`, `gamma_g2`, and the A, H and L
+    /// queries) have no counterpart in the other group and are not checked.
+    /// This costs two pairings per element of `b_g1_query`.
+    pub fn check_crs_consistency(&self, pk: &ProvingKey<E>) -> R1CSResult<bool> {
+        if pk.beta_g1.is_zero()
+            || pk.delta_g1.is_zero()
+            || self.beta_g2.is_zero()
+            || self.delta_g2.is_zero()
+        {
+            return Err(SynthesisError::UnexpectedIdentity);
+        }
+
+        if pk.vk.alpha_g1 != self.alpha_g1
+            || pk.vk.beta_g2 != self.beta_g2
+            || pk.vk.gamma_g2 != self.gamma_g2
+            || pk.vk.delta_g2 != self.delta_g2
+            || pk.vk.gamma_abc_g1 != self.gamma_abc_g1
+        {
+            return Ok(false);
+        }
+
+        if E::pairing(pk.beta_g1, self.delta_g2).0 != E::pairing(pk.delta_g1, self.beta_g2).0 {
+            return Ok(false);
+        }
+
+        if pk.b_g1_query.len() != pk.b_g2_query.len() {
+            return Ok(false);
+        }
+        let b_consistent = pk
+            .b_g1_query
+            .iter()
+            .zip(&pk.b_g2_query)
+            .all(|(b_g1, b_g2)| {
+                E::pairing(*b_g1, self.delta_g2).0 == E::pairing(pk.delta_g1, *b_g2).0
+            });
+
+        Ok(b_consistent)
+    }
+}
+
+/// The format version prepended to keys by [`VerifyingKey::serialize_versioned`]
+/// and [`ProvingKey::serialize_versioned`].
+pub const KEY_FORMAT_VERSION: u8 = 2;
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Serialize the verification key, prefixed with [`KEY_FORMAT_VERSION`].
+    pub fn serialize_versioned<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        KEY_FORMAT_VERSION.serialize_with_mode(&mut writer, compress)?;
+        self.serialize_with_mode(writer, compress)
+    }
+
+    /// Deserialize a verification key written by [`Self::serialize_versioned`]
+    /// under any known format version, upgrading older layouts to the current
+    /// one. The first byte is always read as the version, so unversioned keys
+    /// are not recognized; read those with [`CanonicalDeserialize`].
+    pub fn deserialize_any_version<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        // Version 2 only changed the layout of proving keys.
+        match u8::deserialize_with_mode(&mut reader, compress, validate)? {
+            1 | 2 => Self::deserialize_with_mode(reader, compress, validate),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
 /// Preprocessed verification key parameters that enable faster verification
 /// at the expense of larger size in memory.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -96,14 +273,98 @@ pub struct PreparedVerifyingKey<E: Pairing> {
     pub delta_g2_neg_pc: E::G2Prepared,
 }
 
+impl<E: Pairing> PreparedVerifyingKey<E> {
+    /// Assemble a prepared verification key from externally precomputed
+    /// elements, without computing any pairings. Use [`Self::validate`] to
+    /// check that the elements are consistent with `vk`.
+    pub fn from_parts(
+        vk: VerifyingKey<E>,
+        alpha_g1_beta_g2: E::TargetField,
+        gamma_g2_neg_pc: E::G2Prepared,
+        delta_g2_neg_pc: E::G2Prepared,
+    ) -> Self {
+        Self {
+            vk,
+            alpha_g1_beta_g2,
+            gamma_g2_neg_pc,
+            delta_g2_neg_pc,
+        }
+    }
+
+    /// Check that the precomputed elements are consistent with `self.vk`.
+    pub fn validate(&self) -> bool {
+        let mut expected = Self::from_parts(
+            self.vk.clone(),
+            E::TargetField::default(),
+            E::G2Prepared::default(),
+            E::G2Prepared::default(),
+        );
+        expected.refresh();
+
+        self.alpha_g1_beta_g2 == expected.alpha_g1_beta_g2
+            && self.gamma_g2_neg_pc == expected.gamma_g2_neg_pc
+            && self.delta_g2_neg_pc == expected.delta_g2_neg_pc
+    }
+
+    /// Recompute the precomputed elements from `self.vk`. This must be called
+    /// after modifying `self.vk` in place, since the precomputed elements are
+    /// otherwise left stale.
+    pub fn refresh(&mut self) {
+        self.alpha_g1_beta_g2 = E::pairing(self.vk.alpha_g1, self.vk.beta_g2).0;
+        self.gamma_g2_neg_pc = (-self.vk.gamma_g2.into_group()).into_affine().into();
+        self.delta_g2_neg_pc = (-self.vk.delta_g2.into_group()).into_affine().into();
+    }
+}
+
+/// The operations performed to verify a proof, as reported by
+/// [`PreparedVerifyingKey::verification_cost`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerificationCost {
+    /// The number of Miller loops.
+    pub num_pairings: usize,
+    /// The number of final exponentiations.
+    pub num_final_exponentiations: usize,
+    /// The number of scalar multiplications needed to prepare the public inputs.
+    pub input_msm_size: usize,
+    /// The estimated gas cost of verifying on BN254 via the EVM precompiles
+    /// (EIP-1108 pricing), excluding calldata and transaction overhead. An
+    /// on-chain verifier cannot store `alpha_g1_beta_g2`, so it pays for four
+    /// pairings instead of three.
+    pub estimated_bn254_gas: u64,
+}
+
+impl<E: Pairing> PreparedVerifyingKey<E> {
+    /// Report the operations performed when verifying a proof against `self`.
+    pub fn verification_cost(&self) -> VerificationCost {
+        const PAIRING_BASE_GAS: u64 = 45_000;
+        const PAIRING_PER_PAIR_GAS: u64 = 34_000;
+        const EC_MUL_GAS: u64 = 6_000;
+        const EC_ADD_GAS: u64 = 150;
+
+        let input_msm_size = self.vk.gamma_abc_g1.len().saturating_sub(1);
+        let estimated_bn254_gas = PAIRING_BASE_GAS
+            + 4 * PAIRING_PER_PAIR_GAS
+            + input_msm_size as u64 * (EC_MUL_GAS + EC_ADD_GAS);
+
+        VerificationCost {
+            num_pairings: 3,
+            num_final_exponentiations: 1,
+            input_msm_size,
+            estimated_bn254_gas,
+        }
+    }
+}
+
 impl<E: Pairing> From<PreparedVerifyingKey<E>> for VerifyingKey<E> {
     fn from(other: PreparedVerifyingKey<E>) -> Self {
         other.vk
     }
 }
 
-impl<E: Pairing> From<VerifyingKey<E>> for PreparedVerifyingKey<E> {
-    fn from(other: VerifyingKey<E>) -> Self {
+impl<E: Pairing> TryFrom<VerifyingKey<E>> for PreparedVerifyingKey<E> {
+    type Error = SynthesisError;
+
+    fn try_from(other: VerifyingKey<E>) -> Result<Self, Self::Error> {
         crate::prepare_verifying_key(&other)
     }
 }
@@ -119,10 +380,215 @@ impl<E: Pairing> Default for PreparedVerifyingKey<E> {
     }
 }
 
+/// A prepared verification key that keeps `gamma_abc_g1` in compressed form.
+/// In memory, an affine point stores both coordinates, so this roughly halves
+/// the size of keys with many public inputs. In exchange, every verification
+/// decompresses one point per public input, which costs a square root each.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactVerifyingKey<E: Pairing> {
+    /// The element `e(alpha * G, beta * H)` in `E::GT`.
+    pub alpha_g1_beta_g2: E::TargetField,
+    /// The element `- gamma * H` in `E::G2`, prepared for use in pairings.
+    pub gamma_g2_neg_pc: E::G2Prepared,
+    /// The element `- delta * H` in `E::G2`, prepared for use in pairings.
+    pub delta_g2_neg_pc: E::G2Prepared,
+    /// The concatenated compressed encodings of `gamma_abc_g1`.
+    gamma_abc_g1: Vec<u8>,
+}
+
+impl<E: Pairing> CompactVerifyingKey<E> {
+    fn point_size() -> usize {
+        E::G1Affine::generator().compressed_size()
+    }
+
+    /// The number of elements of `gamma_abc_g1`, i.e. the number of public
+    /// inputs plus one.
+    pub fn gamma_abc_g1_len(&self) -> usize {
+        self.gamma_abc_g1.len() / Self::point_size()
+    }
+
+    /// Decompress the elements of `gamma_abc_g1` one at a time.
+    pub fn gamma_abc_g1(&self) -> impl Iterator<Item = E::G1Affine> + '_ {
+        self.gamma_abc_g1.chunks(Self::point_size()).map(|bytes| {
+            // The bytes were produced from valid points in `From`, so neither
+            // decoding nor the subgroup check can fail.
+            E::G1Affine::deserialize_compressed_unchecked(bytes)
+                .expect("compressed point is well-formed")
+        })
+    }
+}
+
+impl<E: Pairing> From<&PreparedVerifyingKey<E>> for CompactVerifyingKey<E> {
+    fn from(pvk: &PreparedVerifyingKey<E>) -> Self {
+        let mut gamma_abc_g1 = Vec::with_capacity(pvk.vk.gamma_abc_g1.len() * Self::point_size());
+        for point in &pvk.vk.gamma_abc_g1 {
+            point
+                .serialize_compressed(&mut gamma_abc_g1)
+                .expect("serializing to a vector cannot fail");
+        }
+        Self {
+            alpha_g1_beta_g2: pvk.alpha_g1_beta_g2,
+            gamma_g2_neg_pc: pvk.gamma_g2_neg_pc.clone(),
+            delta_g2_neg_pc: pvk.delta_g2_neg_pc.clone(),
+            gamma_abc_g1,
+        }
+    }
+}
+
+/// An error indicating that public inputs do not fit a verifying key.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputError {
+    /// The number of values differs from the number of inputs they are for.
+    LengthMismatch {
+        /// The number of inputs.
+        expected: usize,
+        /// The number of values supplied.
+        actual: usize,
+    },
+    /// An index is not below the number of public inputs.
+    IndexOutOfRange {
+        /// The offending index.
+        index: usize,
+        /// The number of public inputs, excluding the constant one.
+        num_inputs: usize,
+    },
+    /// An index was given more than once.
+    DuplicateIndex(usize),
+    /// The input at this index is not the canonical encoding of a scalar field
+    /// element.
+    NonCanonical(usize),
+    /// An error from the underlying constraint system or verification routine,
+    /// such as a malformed verifying key.
+    Synthesis(SynthesisError),
+}
+
+impl core::fmt::Display for InputError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InputError::LengthMismatch { expected, actual } => {
+                write!(f, "expected {} input values, got {}", expected, actual)
+            },
+            InputError::IndexOutOfRange { index, num_inputs } => write!(
+                f,
+                "input index {} is out of range for {} inputs",
+                index, num_inputs
+            ),
+            InputError::DuplicateIndex(index) => write!(f, "input index {} is repeated", index),
+            InputError::NonCanonical(index) => write!(f, "input {} is not canonical", index),
+            InputError::Synthesis(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InputError {}
+
+impl From<SynthesisError> for InputError {
+    fn from(e: SynthesisError) -> Self {
+        InputError::Synthesis(e)
+    }
+}
+
+/// Public inputs split into fixed inputs, whose contribution to the prepared
+/// inputs is computed once, and volatile inputs, which are supplied anew for
+/// each verification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreparedInputs<E: Pairing> {
+    /// The constant term plus the contribution of the fixed inputs.
+    fixed: E::G1,
+    /// The elements of `gamma_abc_g1` belonging to the volatile inputs, in order.
+    volatile_bases: Vec<E::G1Affine>,
+}
+
+impl<E: Pairing> PreparedInputs<E> {
+    /// Precompute the contribution of the public inputs at `fixed_indices`, which
+    /// take the values `fixed_values`. Indices refer to positions in the public
+    /// input vector, excluding the constant one. The remaining inputs are volatile
+    /// and are supplied in increasing index order to [`Self::update_volatile`].
+    /// A key without `gamma_abc_g1[0]` is reported as
+    /// [`SynthesisError::MalformedVerifyingKey`].
+    pub fn with_fixed(
+        pvk: &PreparedVerifyingKey<E>,
+        fixed_indices: &[usize],
+        fixed_values: &[E::ScalarField],
+    ) -> Result<Self, InputError> {
+        if fixed_indices.len() != fixed_values.len() {
+            return Err(InputError::LengthMismatch {
+                expected: fixed_indices.len(),
+                actual: fixed_values.len(),
+            });
+        }
+
+        let (constant, inputs) = pvk
+            .vk
+            .gamma_abc_g1
+            .split_first()
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        let mut is_fixed = vec![false; inputs.len()];
+        for &i in fixed_indices {
+            if i >= inputs.len() {
+                return Err(InputError::IndexOutOfRange {
+                    index: i,
+                    num_inputs: inputs.len(),
+                });
+            }
+            if is_fixed[i] {
+                return Err(InputError::DuplicateIndex(i));
+            }
+            is_fixed[i] = true;
+        }
+
+        let fixed_bases = fixed_indices.iter().map(|&i| inputs[i]).collect::<Vec<_>>();
+        let fixed_scalars = fixed_values
+            .iter()
+            .map(|v| v.into_bigint())
+            .collect::<Vec<_>>();
+        let fixed = *constant + E::G1::msm_bigint(&fixed_bases, &fixed_scalars);
+
+        let volatile_bases = inputs
+            .iter()
+            .zip(is_fixed)
+            .filter(|(_, is_fixed)| !is_fixed)
+            .map(|(base, _)| *base)
+            .collect();
+
+        Ok(Self {
+            fixed,
+            volatile_bases,
+        })
+    }
+
+    /// The number of volatile inputs expected by [`Self::update_volatile`].
+    pub fn num_volatile(&self) -> usize {
+        self.volatile_bases.len()
+    }
+
+    /// Compute the prepared inputs for use with
+    /// [`Groth16::verify_proof_with_prepared_inputs`](crate::Groth16::verify_proof_with_prepared_inputs),
+    /// given the values of the volatile inputs in increasing index order.
+    pub fn update_volatile(&self, volatile_values: &[E::ScalarField]) -> Result<E::G1, InputError> {
+        if volatile_values.len() != self.volatile_bases.len() {
+            return Err(InputError::LengthMismatch {
+                expected: self.volatile_bases.len(),
+                actual: volatile_values.len(),
+            });
+        }
+
+        let volatile_scalars = volatile_values
+            .iter()
+            .map(|v| v.into_bigint())
+            .collect::<Vec<_>>();
+        Ok(self.fixed + E::G1::msm_bigint(&self.volatile_bases, &volatile_scalars))
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// The prover key for for the Groth16 zkSNARK.
-#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// The canonical encoding ends with `optimization_goal`. Keys written before the
+/// field was added are read with [`Self::deserialize_legacy`].
+#[derive(Clone, Debug, PartialEq)]
 pub struct ProvingKey<E: Pairing> {
     /// The underlying verification key.
     pub vk: VerifyingKey<E>,
@@ -140,4 +606,197 @@ pub struct ProvingKey<E: Pairing> {
     pub h_query: Vec<E::G1Affine>,
     /// The elements `l_i * G` in `E::G1`.
     pub l_query: Vec<E::G1Affine>,
+    /// The optimization goal the circuit was synthesized with at setup, which
+    /// the prover must use as well.
+    pub optimization_goal: SetupOptimizationGoal,
+}
+
+impl<E: Pairing> CanonicalSerialize for ProvingKey<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.vk.serialize_with_mode(&mut writer, compress)?;
+        self.beta_g1.serialize_with_mode(&mut writer, compress)?;
+        self.delta_g1.serialize_with_mode(&mut writer, compress)?;
+        self.a_query.serialize_with_mode(&mut writer, compress)?;
+        self.b_g1_query.serialize_with_mode(&mut writer, compress)?;
+        self.b_g2_query.serialize_with_mode(&mut writer, compress)?;
+        self.h_query.serialize_with_mode(&mut writer, compress)?;
+        self.l_query.serialize_with_mode(&mut writer, compress)?;
+        self.optimization_goal.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.vk.serialized_size(compress)
+            + self.beta_g1.serialized_size(compress)
+            + self.delta_g1.serialized_size(compress)
+            + self.a_query.serialized_size(compress)
+            + self.b_g1_query.serialized_size(compress)
+            + self.b_g2_query.serialized_size(compress)
+            + self.h_query.serialized_size(compress)
+            + self.l_query.serialized_size(compress)
+            + self.optimization_goal.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for ProvingKey<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.vk.check()?;
+        self.beta_g1.check()?;
+        self.delta_g1.check()?;
+        self.a_query.check()?;
+        self.b_g1_query.check()?;
+        self.b_g2_query.check()?;
+        self.h_query.check()?;
+        self.l_query.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for ProvingKey<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut pk = Self::deserialize_legacy(
+            &mut reader,
+            OptimizationGoal::Constraints,
+            compress,
+            validate,
+        )?;
+        pk.optimization_goal =
+            SetupOptimizationGoal::deserialize_with_mode(reader, compress, validate)?;
+        Ok(pk)
+    }
+}
+
+/// The [`OptimizationGoal`] stored in a [`ProvingKey`]. Different goals can lay
+/// out the variables of the same circuit differently, so a proof created with
+/// a goal other than the one used at setup is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetupOptimizationGoal(pub OptimizationGoal);
+
+impl CanonicalSerialize for SetupOptimizationGoal {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        let tag: u8 = match self.0 {
+            OptimizationGoal::None => 0,
+            OptimizationGoal::Constraints => 1,
+            OptimizationGoal::Weight => 2,
+        };
+        tag.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        0u8.serialized_size(compress)
+    }
+}
+
+impl Valid for SetupOptimizationGoal {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for SetupOptimizationGoal {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            0 => Ok(Self(OptimizationGoal::None)),
+            1 => Ok(Self(OptimizationGoal::Constraints)),
+            2 => Ok(Self(OptimizationGoal::Weight)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+/// A summary of the shape of a [`ProvingKey`], which can be checked against
+/// a circuit without handling the (possibly very large) key itself.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CrsDescription {
+    /// The description of the underlying verification key.
+    pub vk: VerifyingKeyDescription,
+    /// The length of `a_query`.
+    pub a_query_len: u64,
+    /// The length of `b_g1_query`.
+    pub b_g1_query_len: u64,
+    /// The length of `b_g2_query`.
+    pub b_g2_query_len: u64,
+    /// The length of `h_query`.
+    pub h_query_len: u64,
+    /// The length of `l_query`.
+    pub l_query_len: u64,
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Describe the shape of the proving key.
+    pub fn describe(&self) -> CrsDescription {
+        CrsDescription {
+            vk: self.vk.describe(),
+            a_query_len: self.a_query.len() as u64,
+            b_g1_query_len: self.b_g1_query.len() as u64,
+            b_g2_query_len: self.b_g2_query.len() as u64,
+            h_query_len: self.h_query.len() as u64,
+            l_query_len: self.l_query.len() as u64,
+        }
+    }
+
+    /// Serialize the proving key, prefixed with [`KEY_FORMAT_VERSION`].
+    pub fn serialize_versioned<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        KEY_FORMAT_VERSION.serialize_with_mode(&mut writer, compress)?;
+        self.serialize_with_mode(writer, compress)
+    }
+
+    /// Deserialize a proving key written by [`Self::serialize_versioned`] with
+    /// format version 2. The first byte is always read as the version, so
+    /// unversioned keys are not recognized: read those with
+    /// [`CanonicalDeserialize`], or with [`Self::deserialize_legacy`] if they
+    /// predate `optimization_goal`. Version 1 keys do not record the goal either,
+    /// and are rejected; read them with [`Self::deserialize_legacy`] after the
+    /// version byte.
+    pub fn deserialize_any_version<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(&mut reader, compress, validate)? {
+            2 => Self::deserialize_with_mode(reader, compress, validate),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    /// Deserialize a proving key in the canonical encoding used before
+    /// `optimization_goal` was added, which lacks the goal. `optimization_goal`
+    /// must be the goal the key was generated with, which was always
+    /// [`OptimizationGoal::Constraints`] before the goal became configurable.
+    pub fn deserialize_legacy<R: Read>(
+        mut reader: R,
+        optimization_goal: OptimizationGoal,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            vk: VerifyingKey::deserialize_with_mode(&mut reader, compress, validate)?,
+            beta_g1: E::G1Affine::deserialize_with_mode(&mut reader, compress, validate)?,
+            delta_g1: E::G1Affine::deserialize_with_mode(&mut reader, compress, validate)?,
+            a_query: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            b_g1_query: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            b_g2_query: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            h_query: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            l_query: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            optimization_goal: SetupOptimizationGoal(optimization_goal),
+        })
+    }
 }
@@ -1,17 +1,107 @@
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16, ProvingKey, Vec, VerifyingKey};
+use crate::{
+    r1cs_to_qap::{DomainSizeError, R1CSToQAP},
+    Groth16, ProvingKey, SetupOptimizationGoal, Vec, VerifyingKey,
+};
 use ark_ec::{pairing::Pairing, scalar_mul::BatchMulPreprocessing, CurveGroup};
 use ark_ff::{Field, UniformRand, Zero};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::r1cs::{
-    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, Result as R1CSResult,
-    SynthesisError, SynthesisMode,
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+    Result as R1CSResult, SynthesisError, SynthesisMode,
 };
-use ark_std::rand::Rng;
-use ark_std::{cfg_into_iter, cfg_iter};
+use ark_std::rand::{Rng, SeedableRng};
+use ark_std::{cfg_into_iter, cfg_iter, vec};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// An error indicating that the supplied toxic waste cannot produce a valid key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToxicWasteError {
+    /// `gamma` is zero, so `gamma_abc_g1` cannot be computed.
+    ZeroGamma,
+    /// `delta` is zero, so the H and L queries cannot be computed.
+    ZeroDelta,
+}
+
+impl core::fmt::Display for ToxicWasteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ToxicWasteError::ZeroGamma => write!(f, "gamma is zero"),
+            ToxicWasteError::ZeroDelta => write!(f, "delta is zero"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ToxicWasteError {}
+
+/// An error returned when generating parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SetupError {
+    /// The supplied toxic waste cannot produce a valid key.
+    ToxicWaste(ToxicWasteError),
+    /// The circuit is too large for the evaluation domains of the reduction.
+    DomainSize(DomainSizeError),
+    /// The constraint system has no instance variables, so it lacks the constant
+    /// one that `gamma_abc_g1[0]` is generated for.
+    MissingConstant,
+    /// Synthesizing or reducing the circuit failed.
+    Synthesis(SynthesisError),
+}
+
+impl core::fmt::Display for SetupError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SetupError::ToxicWaste(e) => write!(f, "{}", e),
+            SetupError::DomainSize(e) => write!(f, "{}", e),
+            SetupError::MissingConstant => {
+                write!(f, "the constraint system has no constant instance variable")
+            },
+            SetupError::Synthesis(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SetupError {}
+
+impl From<ToxicWasteError> for SetupError {
+    fn from(e: ToxicWasteError) -> Self {
+        SetupError::ToxicWaste(e)
+    }
+}
+
+impl From<DomainSizeError> for SetupError {
+    fn from(e: DomainSizeError) -> Self {
+        SetupError::DomainSize(e)
+    }
+}
+
+impl From<SynthesisError> for SetupError {
+    fn from(e: SynthesisError) -> Self {
+        SetupError::Synthesis(e)
+    }
+}
+
+/// Flatten a [`SetupError`] for the [`SNARK`](ark_crypto_primitives::snark::SNARK)
+/// interface, whose error type is [`SynthesisError`]. Bad toxic waste becomes
+/// [`SynthesisError::UnexpectedIdentity`], an oversized circuit
+/// [`SynthesisError::PolynomialDegreeTooLarge`], and a missing constant
+/// [`SynthesisError::MalformedVerifyingKey`].
+impl From<SetupError> for SynthesisError {
+    fn from(e: SetupError) -> Self {
+        match e {
+            SetupError::ToxicWaste(_) => SynthesisError::UnexpectedIdentity,
+            SetupError::DomainSize(e) => e.into(),
+            SetupError::MissingConstant => SynthesisError::MalformedVerifyingKey,
+            SetupError::Synthesis(e) => e,
+        }
+    }
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Generates a random common reference string for
     /// a circuit using the provided R1CS-to-QAP reduction.
@@ -19,10 +109,75 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     pub fn generate_random_parameters_with_reduction<C>(
         circuit: C,
         rng: &mut impl Rng,
-    ) -> R1CSResult<ProvingKey<E>>
+    ) -> Result<ProvingKey<E>, SetupError>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
+        Self::generate_random_parameters_with_options(
+            circuit,
+            false,
+            OptimizationGoal::Constraints,
+            rng,
+        )
+    }
+
+    /// Generates a random common reference string for a circuit using the
+    /// provided R1CS-to-QAP reduction and the optimization goal `goal`. Proofs
+    /// must be created with the same goal, e.g. by
+    /// [`Self::create_proof_with_reduction_and_goal`].
+    #[inline]
+    pub fn generate_random_parameters_with_goal<C>(
+        circuit: C,
+        goal: OptimizationGoal,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::generate_random_parameters_with_options(circuit, false, goal, rng)
+    }
+
+    /// Generates a random common reference string for a circuit using the
+    /// provided R1CS-to-QAP reduction, omitting `b_g1_query`. The prover only
+    /// needs `b_g1_query` when `r != 0`, so the resulting smaller key can only
+    /// be used to create proofs with `r = 0`, which are *not* zero-knowledge.
+    #[inline]
+    pub fn generate_random_parameters_without_b_g1_query<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::generate_random_parameters_with_options(
+            circuit,
+            true,
+            OptimizationGoal::Constraints,
+            rng,
+        )
+    }
+
+    #[inline]
+    fn generate_random_parameters_with_options<C>(
+        circuit: C,
+        omit_b_g1_query: bool,
+        goal: OptimizationGoal,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = Self::synthesize_for_setup(circuit, goal)?;
+        Self::generate_random_parameters_from_cs(cs, omit_b_g1_query, rng)
+    }
+
+    /// Generates a random common reference string for the finalized constraint
+    /// system `cs`, which may have been synthesized with or without a witness.
+    pub(crate) fn generate_random_parameters_from_cs(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        omit_b_g1_query: bool,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError> {
         let alpha = E::ScalarField::rand(rng);
         let beta = E::ScalarField::rand(rng);
         let gamma = E::ScalarField::rand(rng);
@@ -31,14 +186,15 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let g1_generator = E::G1::rand(rng);
         let g2_generator = E::G2::rand(rng);
 
-        Self::generate_parameters_with_qap(
-            circuit,
+        Self::generate_parameters_from_cs(
+            cs,
             alpha,
             beta,
             gamma,
             delta,
             g1_generator,
             g2_generator,
+            omit_b_g1_query,
             rng,
         )
     }
@@ -53,15 +209,167 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         g1_generator: E::G1,
         g2_generator: E::G2,
         rng: &mut impl Rng,
-    ) -> R1CSResult<ProvingKey<E>>
+    ) -> Result<ProvingKey<E>, SetupError>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
-        type D<F> = GeneralEvaluationDomain<F>;
+        Self::generate_parameters_with_qap_and_options(
+            circuit,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            g1_generator,
+            g2_generator,
+            false,
+            rng,
+        )
+    }
 
-        let setup_time = start_timer!(|| "Groth16::Generator");
+    /// Derive a pair of group generators deterministically from `domain`, e.g. an identifier
+    /// of the circuit. The generators are sampled by `E::G1::rand` and `E::G2::rand`, which
+    /// map random x-coordinates to curve points, from a [`ChaCha20Rng`] seeded with the
+    /// SHA-256 hash of `domain`. Nobody knows their discrete logarithms with respect to any
+    /// other point, so no party can choose them adversarially. ChaCha20 is pinned rather
+    /// than `StdRng`, whose algorithm may change between `rand` releases.
+    pub fn nums_generators(domain: &[u8]) -> (E::G1, E::G2) {
+        let mut rng = ChaCha20Rng::from_seed(Sha256::digest(domain).into());
+        (E::G1::rand(&mut rng), E::G2::rand(&mut rng))
+    }
+
+    /// Create parameters for a circuit, given some toxic waste, using generators derived
+    /// from `domain` by [`Self::nums_generators`] instead of sampled ones.
+    pub fn generate_parameters_nums_generators<C>(
+        circuit: C,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        domain: &[u8],
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let (g1_generator, g2_generator) = Self::nums_generators(domain);
+        Self::generate_parameters_with_qap(
+            circuit,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            g1_generator,
+            g2_generator,
+            rng,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn generate_parameters_with_qap_and_options<C>(
+        circuit: C,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        omit_b_g1_query: bool,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = Self::synthesize_for_setup(circuit, OptimizationGoal::Constraints)?;
+        Self::generate_parameters_from_cs(
+            cs,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            g1_generator,
+            g2_generator,
+            omit_b_g1_query,
+            rng,
+        )
+    }
+
+    /// Check that `gamma` and `delta` are invertible, as required by
+    /// [`Self::generate_parameters_with_qap`], which runs this check first and
+    /// returns its error as [`SetupError::ToxicWaste`].
+    pub fn check_toxic_waste(
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+    ) -> Result<(), ToxicWasteError> {
+        if gamma.is_zero() {
+            return Err(ToxicWasteError::ZeroGamma);
+        }
+        if delta.is_zero() {
+            return Err(ToxicWasteError::ZeroDelta);
+        }
+        Ok(())
+    }
+
+    /// Compute the densities `(non_zero_a, non_zero_b)` of the A and B queries that the
+    /// generator derives for the finalized constraint system `cs`, directly from its
+    /// constraint matrices. This can be used to size the generator's window tables
+    /// ahead of a full setup. It assumes the layout of
+    /// [`LibsnarkReduction`](crate::r1cs_to_qap::LibsnarkReduction), in which every
+    /// instance variable has a non-zero A entry. The generator's counts differ only if
+    /// an evaluation vanishes at the sampled point, which happens with negligible
+    /// probability. Returns [`SynthesisError::MissingCS`] if `cs` holds no
+    /// constraint system.
+    pub fn query_densities(cs: ConstraintSystemRef<E::ScalarField>) -> R1CSResult<(usize, usize)> {
+        let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+        let num_instance_variables = cs.num_instance_variables();
+        // Like the generator, skip the last QAP variable.
+        let qap_num_variables = (num_instance_variables - 1) + cs.num_witness_variables();
+
+        let mut in_a = vec![false; qap_num_variables + 1];
+        let mut in_b = vec![false; qap_num_variables + 1];
+        in_a[..num_instance_variables]
+            .iter_mut()
+            .for_each(|in_a| *in_a = true);
+        for row in &matrices.a {
+            for &(_, index) in row {
+                in_a[index] = true;
+            }
+        }
+        for row in &matrices.b {
+            for &(_, index) in row {
+                in_b[index] = true;
+            }
+        }
+
+        let non_zero_a = in_a[..qap_num_variables].iter().filter(|x| **x).count();
+        let non_zero_b = in_b[..qap_num_variables].iter().filter(|x| **x).count();
+        Ok((non_zero_a, non_zero_b))
+    }
+
+    /// Compute the number of scalars the G1 window table is used for, failing instead of
+    /// wrapping around if the counts are too large, which would under-size the table.
+    pub(crate) fn g1_table_size(
+        non_zero_a: usize,
+        num_b_g1_scalars: usize,
+        qap_num_variables: usize,
+        m_raw: usize,
+    ) -> R1CSResult<usize> {
+        non_zero_a
+            .checked_add(num_b_g1_scalars)
+            .and_then(|n| n.checked_add(qap_num_variables))
+            .and_then(|n| n.checked_add(m_raw))
+            .and_then(|n| n.checked_add(1))
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)
+    }
+
+    fn synthesize_for_setup<C>(
+        circuit: C,
+        goal: OptimizationGoal,
+    ) -> R1CSResult<ConstraintSystemRef<E::ScalarField>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let cs = ConstraintSystem::new_ref();
-        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_optimization_goal(goal);
         cs.set_mode(SynthesisMode::Setup);
 
         // Synthesize the circuit.
@@ -73,6 +381,32 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         cs.finalize();
         end_timer!(lc_time);
 
+        Ok(cs)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn generate_parameters_from_cs(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        omit_b_g1_query: bool,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, SetupError> {
+        type D<F> = GeneralEvaluationDomain<F>;
+
+        Self::check_toxic_waste(gamma, delta)?;
+
+        // `gamma_abc_g1[0]` must exist to account for the constant one.
+        if cs.num_instance_variables() == 0 {
+            return Err(SetupError::MissingConstant);
+        }
+
+        let setup_time = start_timer!(|| "Groth16::Generator");
+
         // Following is the mapping of symbols from the Groth16 paper to this implementation
         // l -> num_instance_variables
         // m -> qap_num_variables
@@ -85,7 +419,10 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         ///////////////////////////////////////////////////////////////////////////
         let domain_time = start_timer!(|| "Constructing evaluation domain");
 
-        let domain_size = cs.num_constraints() + cs.num_instance_variables();
+        let domain_size = QAP::check_domain_size::<E::ScalarField>(
+            cs.num_constraints(),
+            cs.num_instance_variables(),
+        )?;
         let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
         let t = domain.sample_element_outside_domain(rng);
 
@@ -94,6 +431,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         let reduction_time = start_timer!(|| "R1CS to QAP Instance Map with Evaluation");
         let num_instance_variables = cs.num_instance_variables();
+        let optimization_goal = cs.optimization_goal();
         let (a, b, c, zt, qap_num_variables, m_raw) =
             QAP::instance_map_with_evaluation::<E::ScalarField, D<E::ScalarField>>(cs, &t)?;
         end_timer!(reduction_time);
@@ -107,8 +445,8 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             .map(|i| usize::from(!b[i].is_zero()))
             .sum();
 
-        let gamma_inverse = gamma.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
-        let delta_inverse = delta.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
+        let gamma_inverse = gamma.inverse().expect("checked by check_toxic_waste");
+        let delta_inverse = delta.inverse().expect("checked by check_toxic_waste");
 
         let gamma_abc = cfg_iter!(a[..num_instance_variables])
             .zip(&b[..num_instance_variables])
@@ -137,7 +475,9 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         // Compute G window table
         let g1_window_time = start_timer!(|| "Compute G1 window table");
-        let num_scalars = non_zero_a + non_zero_b + qap_num_variables + m_raw + 1;
+        let num_b_g1_scalars = if omit_b_g1_query { 0 } else { non_zero_b };
+        let num_scalars =
+            Self::g1_table_size(non_zero_a, num_b_g1_scalars, qap_num_variables, m_raw)?;
         let g1_table = BatchMulPreprocessing::new(g1_generator, num_scalars);
         end_timer!(g1_window_time);
 
@@ -150,39 +490,37 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let delta_g1 = g1_generator * &delta;
         let delta_g2 = g2_generator * &delta;
 
-        // Compute the A-query
-        let a_time = start_timer!(|| "Calculate A");
-        let a_query = g1_table.batch_mul(&a);
-        drop(a);
-        end_timer!(a_time);
-
-        // Compute the B-query in G1
-        let b_g1_time = start_timer!(|| "Calculate B G1");
-        let b_g1_query = g1_table.batch_mul(&b);
-        drop(b);
-        end_timer!(b_g1_time);
-
-        // Compute the H-query
-        let h_time = start_timer!(|| "Calculate H");
         let h_scalars =
             QAP::h_query_scalars::<_, D<E::ScalarField>>(m_raw - 1, t, zt, delta_inverse)?;
-        let h_query = g1_table.batch_mul(&h_scalars);
-        end_timer!(h_time);
+        let b_g1_scalars = if omit_b_g1_query {
+            drop(b);
+            Vec::new()
+        } else {
+            b
+        };
 
-        // Compute the L-query
-        let l_time = start_timer!(|| "Calculate L");
-        let l_query = g1_table.batch_mul(&l);
-        drop(l);
-        end_timer!(l_time);
+        // Compute the A-, B-, H- and L-queries and `gamma_abc_g1`, which share
+        // `g1_table`. Each set of scalars is dropped once its query is computed. When
+        // `parallel` is enabled, the queries are computed concurrently, so all sets of
+        // scalars can be alive next to their queries at once. This trades a higher
+        // peak memory than computing them one at a time for using all threads on the
+        // smaller queries.
+        let g1_queries_time = start_timer!(|| "Calculate A, B G1, H, L and gamma_abc");
+        let scalars = vec![a, b_g1_scalars, h_scalars, l, gamma_abc];
+        let [a_query, b_g1_query, h_query, l_query, gamma_abc_g1]: [Vec<E::G1Affine>; 5] =
+            cfg_into_iter!(scalars)
+                .map(|scalars| g1_table.batch_mul(&scalars))
+                .collect::<Vec<_>>()
+                .try_into()
+                .expect("one query per set of scalars");
+        drop(g1_table);
+        end_timer!(g1_queries_time);
 
         end_timer!(proving_key_time);
 
         // Generate R1CS verification key
         let verifying_key_time = start_timer!(|| "Generate the R1CS verification key");
         let gamma_g2 = g2_generator * &gamma;
-        let gamma_abc_g1 = g1_table.batch_mul(&gamma_abc);
-        drop(g1_table);
-
         end_timer!(verifying_key_time);
 
         let vk = VerifyingKey::<E> {
@@ -204,6 +542,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             b_g2_query,
             h_query,
             l_query,
+            optimization_goal: SetupOptimizationGoal(optimization_goal),
         })
     }
 }
@@ -1,6 +1,14 @@
 //! An implementation of the [`Groth16`] zkSNARK.
 //!
 //! [`Groth16`]: https://eprint.iacr.org/2016/260.pdf
+//!
+//! With the `zeroize` feature, the prover and the provided R1CS-to-QAP reductions
+//! overwrite the buffers they derive from the witness, such as the assembled
+//! assignment and the quotient polynomial, before freeing them, on every path
+//! including errors and cancellation. This does not cover the caller's
+//! assignment, the constraint system synthesized from the circuit, the
+//! randomness `r` and `s`, temporaries inside `ark-ff`, `ark-poly` and `ark-ec`,
+//! or values left in registers and on the stack.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(
     unused,
@@ -34,6 +42,17 @@ pub mod prover;
 /// Verify proofs for the Groth16 zkSNARK construction.
 pub mod verifier;
 
+/// Multi-scalar multiplication backends used by the prover.
+pub mod msm;
+
+/// Contributions to a multi-party setup ceremony for the Groth16 zkSNARK.
+pub mod mpc;
+
+/// Free functions with the signatures of the upstream `ark-groth16` API, for
+/// migrating call sites unchanged. Errors are reported as [`SynthesisError`],
+/// and proving keys use the upstream canonical encoding.
+pub mod compat;
+
 /// Constraints for the Groth16 verifier.
 #[cfg(feature = "r1cs")]
 pub mod constraints;
@@ -78,13 +97,13 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
         circuit: C,
         rng: &mut R,
     ) -> Result<Self::Proof, Self::Error> {
-        Self::create_random_proof_with_reduction(circuit, pk, rng)
+        prover::uncancelled(Self::create_random_proof_with_reduction(circuit, pk, rng))
     }
 
     fn process_vk(
         circuit_vk: &Self::VerifyingKey,
     ) -> Result<Self::ProcessedVerifyingKey, Self::Error> {
-        Ok(prepare_verifying_key(circuit_vk))
+        prepare_verifying_key(circuit_vk)
     }
 
     fn verify_with_processed_vk(
@@ -0,0 +1,142 @@
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, ProvingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    rand::{Rng, SeedableRng},
+    vec::Vec,
+};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+/// A proof that a participant of a setup ceremony knows the secret `x` by which
+/// they multiplied `delta`, following the second phase of
+/// [\[BGM17\]](https://eprint.iacr.org/2017/1050).
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ContributionProof<E: Pairing> {
+    /// A random element `s` of `E::G1`.
+    pub s: E::G1Affine,
+    /// The element `x * s`.
+    pub s_x: E::G1Affine,
+    /// The element `x * r`, where `r` in `E::G2` is derived from the transcript.
+    pub r_x: E::G2Affine,
+}
+
+/// Derive the element `r` in `E::G2` from `s`, `s_x`, and the `delta_g1` that was
+/// updated, so that the contributor cannot choose it. Like
+/// [`Groth16::nums_generators`], this samples `E::G2::rand` from a [`ChaCha20Rng`]
+/// seeded with a SHA-256 hash, here of the compressed points.
+pub(crate) fn transcript_g2<E: Pairing>(
+    s: &E::G1Affine,
+    s_x: &E::G1Affine,
+    delta_g1: &E::G1Affine,
+) -> E::G2Affine {
+    let mut bytes = Vec::new();
+    for point in [s, s_x, delta_g1] {
+        point
+            .serialize_compressed(&mut bytes)
+            .expect("serializing to a vector cannot fail");
+    }
+    let mut rng = ChaCha20Rng::from_seed(Sha256::digest(&bytes).into());
+    E::G2::rand(&mut rng).into_affine()
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Update the circuit-specific parameters `pk` with a fresh secret `x` sampled
+    /// from `rng`, replacing `delta` by `x * delta`. The resulting key is as secure as
+    /// the strongest contribution so far, provided `pk` stems from a trusted
+    /// circuit-independent phase. The returned proof lets others check the update
+    /// with [`Self::verify_contribution`]; `x` is discarded.
+    pub fn contribute(
+        pk: &ProvingKey<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<(ProvingKey<E>, ContributionProof<E>)> {
+        let x = E::ScalarField::rand(rng);
+        let x_inverse = x.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        let mut updated = pk.clone();
+        updated.delta_g1 = (pk.delta_g1 * x).into_affine();
+        updated.vk.delta_g2 = (pk.vk.delta_g2 * x).into_affine();
+        updated.h_query = E::G1::normalize_batch(
+            &pk.h_query
+                .iter()
+                .map(|h| *h * x_inverse)
+                .collect::<Vec<_>>(),
+        );
+        updated.l_query = E::G1::normalize_batch(
+            &pk.l_query
+                .iter()
+                .map(|l| *l * x_inverse)
+                .collect::<Vec<_>>(),
+        );
+
+        let s = E::G1::rand(rng).into_affine();
+        let s_x = (s * x).into_affine();
+        let r = transcript_g2::<E>(&s, &s_x, &pk.delta_g1);
+        let r_x = (r * x).into_affine();
+
+        Ok((updated, ContributionProof { s, s_x, r_x }))
+    }
+
+    /// Check that `after` results from `before` by a contribution for which `proof`
+    /// proves knowledge of the secret. The `H` and `L` queries are checked with a
+    /// random linear combination drawn from `rng`, so this costs a constant number of
+    /// pairings and two MSMs.
+    pub fn verify_contribution(
+        before: &ProvingKey<E>,
+        after: &ProvingKey<E>,
+        proof: &ContributionProof<E>,
+        rng: &mut impl Rng,
+    ) -> bool {
+        // Everything but the delta-dependent elements must be unchanged.
+        if before.vk.alpha_g1 != after.vk.alpha_g1
+            || before.vk.beta_g2 != after.vk.beta_g2
+            || before.vk.gamma_g2 != after.vk.gamma_g2
+            || before.vk.gamma_abc_g1 != after.vk.gamma_abc_g1
+            || before.beta_g1 != after.beta_g1
+            || before.a_query != after.a_query
+            || before.b_g1_query != after.b_g1_query
+            || before.b_g2_query != after.b_g2_query
+            || before.optimization_goal != after.optimization_goal
+            || before.h_query.len() != after.h_query.len()
+            || before.l_query.len() != after.l_query.len()
+        {
+            return false;
+        }
+
+        if proof.s.is_zero() || after.delta_g1.is_zero() || after.vk.delta_g2.is_zero() {
+            return false;
+        }
+
+        // `s_x` and `r_x` use the same secret `x`.
+        let r = transcript_g2::<E>(&proof.s, &proof.s_x, &before.delta_g1);
+        if E::pairing(proof.s, proof.r_x).0 != E::pairing(proof.s_x, r).0 {
+            return false;
+        }
+
+        // `delta_g1` was multiplied by `x`.
+        if E::pairing(before.delta_g1, proof.r_x).0 != E::pairing(after.delta_g1, r).0 {
+            return false;
+        }
+
+        // `delta_g2` was multiplied by the same factor as `delta_g1`.
+        if E::pairing(after.delta_g1, before.vk.delta_g2).0
+            != E::pairing(before.delta_g1, after.vk.delta_g2).0
+        {
+            return false;
+        }
+
+        // The `H` and `L` queries were divided by that factor.
+        let coeffs = (0..before.h_query.len() + before.l_query.len())
+            .map(|_| E::ScalarField::rand(rng).into_bigint())
+            .collect::<Vec<_>>();
+        let old_bases = [before.h_query.as_slice(), &before.l_query].concat();
+        let new_bases = [after.h_query.as_slice(), &after.l_query].concat();
+        let old_combination = E::G1::msm_bigint(&old_bases, &coeffs);
+        let new_combination = E::G1::msm_bigint(&new_bases, &coeffs);
+
+        E::pairing(new_combination, after.vk.delta_g2).0
+            == E::pairing(old_combination, before.vk.delta_g2).0
+    }
+}
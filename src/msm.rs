@@ -0,0 +1,75 @@
+use ark_ec::VariableBaseMSM;
+use ark_ff::PrimeField;
+use ark_std::Zero;
+
+/// The number of terms below which [`ThresholdMsm`] computes an MSM serially
+/// by default.
+pub const SERIAL_MSM_THRESHOLD: usize = 32;
+
+/// A backend for the multi-scalar multiplications computed by the prover. This
+/// allows replacing the arkworks implementation, e.g. with one running on
+/// dedicated hardware, without modifying the prover.
+pub trait MsmBackend {
+    /// Compute `sum_i scalars[i] * bases[i]`.
+    fn msm_bigint<G: VariableBaseMSM>(
+        &self,
+        bases: &[G::MulBase],
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G;
+}
+
+/// The default backend, which delegates to [`VariableBaseMSM::msm_bigint`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArkworksMsm;
+
+impl MsmBackend for ArkworksMsm {
+    #[inline]
+    fn msm_bigint<G: VariableBaseMSM>(
+        &self,
+        bases: &[G::MulBase],
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G {
+        G::msm_bigint(bases, scalars)
+    }
+}
+
+/// A backend that computes MSMs with fewer than `serial_threshold` terms as a plain
+/// sum of scalar multiplications on the current thread, and delegates larger ones to
+/// [`VariableBaseMSM::msm_bigint`]. Under the `parallel` feature, this avoids paying
+/// the thread-pool overhead on inputs too small to benefit from it. The result does
+/// not depend on the threshold.
+#[derive(Clone, Copy, Debug)]
+pub struct ThresholdMsm {
+    /// The number of terms below which MSMs are computed serially.
+    pub serial_threshold: usize,
+}
+
+impl Default for ThresholdMsm {
+    fn default() -> Self {
+        Self {
+            serial_threshold: SERIAL_MSM_THRESHOLD,
+        }
+    }
+}
+
+impl MsmBackend for ThresholdMsm {
+    fn msm_bigint<G: VariableBaseMSM>(
+        &self,
+        bases: &[G::MulBase],
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G {
+        if bases.len().min(scalars.len()) >= self.serial_threshold {
+            return G::msm_bigint(bases, scalars);
+        }
+
+        let mut acc = G::zero();
+        for (base, scalar) in bases.iter().zip(scalars) {
+            // Scalars produced by `into_bigint` are always reduced.
+            match G::ScalarField::from_bigint(*scalar) {
+                Some(scalar) => acc += *base * scalar,
+                None => return G::msm_bigint(bases, scalars),
+            }
+        }
+        acc
+    }
+}
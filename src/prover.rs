@@ -1,23 +1,216 @@
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16, Proof, ProvingKey, VerifyingKey};
+use crate::{
+    generator::SetupError,
+    msm::{MsmBackend, ThresholdMsm},
+    r1cs_to_qap::{DomainSizeError, R1CSToQAP},
+    Groth16, Proof, ProvingKey, VerifyingKey,
+};
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
-use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
 use ark_poly::GeneralEvaluationDomain;
 use ark_relations::r1cs::{
     ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal,
-    Result as R1CSResult,
+    Result as R1CSResult, SynthesisError, Variable,
 };
-use ark_std::rand::Rng;
+use ark_std::rand::{Rng, SeedableRng};
 use ark_std::{
     cfg_into_iter, cfg_iter,
     ops::{AddAssign, Mul},
     vec::Vec,
 };
+use core::sync::atomic::{AtomicBool, Ordering};
+use rand_chacha::ChaCha20Rng;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 type D<F> = GeneralEvaluationDomain<F>;
 
+/// Free a buffer derived from the witness, overwriting it first with the
+/// `zeroize` feature. See the crate documentation for what this covers.
+#[cfg(feature = "zeroize")]
+#[inline]
+pub(crate) fn wipe<T: zeroize::Zeroize>(mut buffer: Vec<T>) {
+    buffer.zeroize();
+}
+
+#[cfg(not(feature = "zeroize"))]
+#[inline]
+pub(crate) fn wipe<T>(_buffer: Vec<T>) {}
+
+/// An error returned when creating a proof.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProvingError {
+    /// Proving was cancelled before the proof was finished.
+    Cancelled,
+    /// The circuit was synthesized with a different optimization goal than the
+    /// one the proving key was generated with.
+    OptimizationGoalMismatch {
+        /// The goal recorded in the proving key.
+        key: OptimizationGoal,
+        /// The goal requested for proving.
+        prover: OptimizationGoal,
+    },
+    /// The number of instance variables, including the constant one, differs
+    /// from the one the proving key was generated for.
+    InstanceLengthMismatch {
+        /// The number of instance variables in the proving key.
+        expected: usize,
+        /// The number of instance variables supplied.
+        actual: usize,
+    },
+    /// The number of witness variables differs from the one the proving key
+    /// was generated for.
+    WitnessLengthMismatch {
+        /// The number of witness variables in the proving key.
+        expected: usize,
+        /// The number of witness variables supplied.
+        actual: usize,
+    },
+    /// The number of coefficients of `h` does not match the length of `h_query`,
+    /// so the circuit needs a different evaluation domain than the proving key.
+    QuotientLengthMismatch {
+        /// The number of coefficients the proving key supports.
+        expected: usize,
+        /// The number of coefficients supplied.
+        actual: usize,
+    },
+    /// The proving key has no `b_g1_query`, so it only supports `r = 0`.
+    MissingBG1Query,
+    /// The circuit is too large for the evaluation domains of the reduction.
+    DomainSize(DomainSizeError),
+    /// Generating the proving key failed, in [`Groth16::setup_and_prove`].
+    Setup(SetupError),
+    /// Synthesizing the circuit or computing the witness map failed.
+    Synthesis(SynthesisError),
+}
+
+impl core::fmt::Display for ProvingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProvingError::Cancelled => write!(f, "proving was cancelled"),
+            ProvingError::OptimizationGoalMismatch { key, prover } => write!(
+                f,
+                "the key was generated with {:?} but proving used {:?}",
+                key, prover
+            ),
+            ProvingError::InstanceLengthMismatch { expected, actual } => write!(
+                f,
+                "expected {} instance variables, got {}",
+                expected, actual
+            ),
+            ProvingError::WitnessLengthMismatch { expected, actual } => {
+                write!(f, "expected {} witness variables, got {}", expected, actual)
+            },
+            ProvingError::QuotientLengthMismatch { expected, actual } => {
+                write!(f, "expected {} coefficients of h, got {}", expected, actual)
+            },
+            ProvingError::MissingBG1Query => {
+                write!(f, "the key has no b_g1_query, which r != 0 requires")
+            },
+            ProvingError::DomainSize(e) => write!(f, "{}", e),
+            ProvingError::Setup(e) => write!(f, "{}", e),
+            ProvingError::Synthesis(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProvingError {}
+
+impl From<SynthesisError> for ProvingError {
+    fn from(e: SynthesisError) -> Self {
+        ProvingError::Synthesis(e)
+    }
+}
+
+impl From<DomainSizeError> for ProvingError {
+    fn from(e: DomainSizeError) -> Self {
+        ProvingError::DomainSize(e)
+    }
+}
+
+impl From<SetupError> for ProvingError {
+    fn from(e: SetupError) -> Self {
+        ProvingError::Setup(e)
+    }
+}
+
+/// A phase of proof generation, reported once it has finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingStage {
+    /// The circuit was synthesized.
+    Synthesized,
+    /// The witness map computed the coefficients of `h`.
+    WitnessMapped,
+    /// The `h` and witness terms of `C` were accumulated.
+    CAccumulated,
+    /// `A` was computed.
+    AComputed,
+    /// `B` was computed.
+    BComputed,
+    /// `C` was computed, which completes the proof.
+    CComputed,
+}
+
+/// A checkpoint after each phase of proving, which returns an error to stop.
+type Checkpoint<'a> = &'a mut dyn FnMut(ProvingStage) -> Result<(), ProvingError>;
+
+/// Flatten the result of proving without cancellation for the
+/// [`SNARK`](ark_crypto_primitives::snark::SNARK) interface, whose error type is
+/// [`SynthesisError`]. A proving key that does not fit the circuit becomes
+/// [`SynthesisError::MalformedVerifyingKey`], or
+/// [`SynthesisError::PolynomialDegreeTooLarge`] if it only differs in the size of
+/// the evaluation domain.
+pub(crate) fn uncancelled<T>(result: Result<T, ProvingError>) -> R1CSResult<T> {
+    result.map_err(|e| match e {
+        ProvingError::Cancelled => unreachable!("proving without cancellation was cancelled"),
+        ProvingError::OptimizationGoalMismatch { .. }
+        | ProvingError::InstanceLengthMismatch { .. }
+        | ProvingError::WitnessLengthMismatch { .. }
+        | ProvingError::MissingBG1Query => SynthesisError::MalformedVerifyingKey,
+        ProvingError::QuotientLengthMismatch { .. } => SynthesisError::PolynomialDegreeTooLarge,
+        ProvingError::DomainSize(e) => e.into(),
+        ProvingError::Setup(e) => e.into(),
+        ProvingError::Synthesis(e) => e,
+    })
+}
+
+/// Check that `num_instance_variables`, including the constant one, and
+/// `num_witness_variables` match the layout `pk` was generated for.
+fn check_layout<E: Pairing>(
+    pk: &ProvingKey<E>,
+    num_instance_variables: usize,
+    num_witness_variables: usize,
+) -> Result<(), ProvingError> {
+    if num_instance_variables != pk.vk.gamma_abc_g1.len() {
+        return Err(ProvingError::InstanceLengthMismatch {
+            expected: pk.vk.gamma_abc_g1.len(),
+            actual: num_instance_variables,
+        });
+    }
+    if num_witness_variables != pk.l_query.len() {
+        return Err(ProvingError::WitnessLengthMismatch {
+            expected: pk.l_query.len(),
+            actual: num_witness_variables,
+        });
+    }
+    Ok(())
+}
+
+/// Check that `h` has the number of coefficients `pk` was generated for.
+fn check_quotient_length<E: Pairing>(
+    pk: &ProvingKey<E>,
+    h: &[E::ScalarField],
+) -> Result<(), ProvingError> {
+    if h.len() != pk.h_query.len() + 1 {
+        return Err(ProvingError::QuotientLengthMismatch {
+            expected: pk.h_query.len() + 1,
+            actual: h.len(),
+        });
+    }
+    Ok(())
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Create a Groth16 proof using randomness `r` and `s` and
     /// the provided R1CS-to-QAP reduction, using the provided
@@ -31,7 +224,22 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         num_inputs: usize,
         num_constraints: usize,
         full_assignment: &[E::ScalarField],
-    ) -> R1CSResult<Proof<E>> {
+    ) -> Result<Proof<E>, ProvingError> {
+        if full_assignment.len() < num_inputs {
+            return Err(ProvingError::InstanceLengthMismatch {
+                expected: num_inputs,
+                actual: full_assignment.len(),
+            });
+        }
+        // The matrices index into `full_assignment`, so both must match the key.
+        check_layout(pk, num_inputs, full_assignment.len() - num_inputs)?;
+        check_layout(
+            pk,
+            matrices.num_instance_variables,
+            matrices.num_witness_variables,
+        )?;
+        QAP::check_domain_size::<E::ScalarField>(num_constraints, num_inputs)?;
+
         let prover_time = start_timer!(|| "Groth16::Prover");
         let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
         let h = QAP::witness_map_from_matrices::<E::ScalarField, D<E::ScalarField>>(
@@ -43,62 +251,175 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         end_timer!(witness_map_time);
         let input_assignment = &full_assignment[1..num_inputs];
         let aux_assignment = &full_assignment[num_inputs..];
-        let proof =
-            Self::create_proof_with_assignment(pk, r, s, &h, input_assignment, aux_assignment)?;
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            r,
+            s,
+            &h,
+            input_assignment,
+            aux_assignment,
+            &ThresholdMsm::default(),
+        );
+        wipe(h);
+        let proof = proof?;
         end_timer!(prover_time);
 
         Ok(proof)
     }
 
+    /// Create a Groth16 proof using randomness `r` and `s` from precomputed assignments,
+    /// without a circuit. `h` holds the coefficients of the quotient polynomial, as
+    /// computed by [`R1CSToQAP::witness_map`], `input_assignment` the public inputs without
+    /// the constant one, and `aux_assignment` the witness variables.
+    pub fn prove_from_assignments(
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        h: &[E::ScalarField],
+        input_assignment: &[E::ScalarField],
+        aux_assignment: &[E::ScalarField],
+    ) -> Result<Proof<E>, ProvingError> {
+        check_layout(pk, input_assignment.len() + 1, aux_assignment.len())?;
+
+        Self::create_proof_with_assignment(
+            pk,
+            r,
+            s,
+            h,
+            input_assignment,
+            aux_assignment,
+            &ThresholdMsm::default(),
+        )
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, using the provided R1CS constraint matrices instead
+    /// of synthesizing a circuit. The matrices must be those of the circuit the
+    /// proving key was generated for, otherwise a layout error is returned.
+    ///
+    /// This is a convenience wrapper around
+    /// [`Self::create_proof_with_reduction_and_matrices`], not a streaming prover:
+    /// before proving starts, `witness` is called exactly once for each instance
+    /// variable and then each witness variable, in index order, and the full
+    /// assignment is held in memory.
+    pub fn create_proof_with_witness_oracle(
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        matrices: &ConstraintMatrices<E::ScalarField>,
+        mut witness: impl FnMut(Variable) -> E::ScalarField,
+    ) -> Result<Proof<E>, ProvingError> {
+        let full_assignment = core::iter::once(E::ScalarField::one())
+            .chain((1..matrices.num_instance_variables).map(|i| witness(Variable::Instance(i))))
+            .chain((0..matrices.num_witness_variables).map(|i| witness(Variable::Witness(i))))
+            .collect::<Vec<_>>();
+
+        let proof = Self::create_proof_with_reduction_and_matrices(
+            pk,
+            r,
+            s,
+            matrices,
+            matrices.num_instance_variables,
+            matrices.num_constraints,
+            &full_assignment,
+        );
+        wipe(full_assignment);
+        proof
+    }
+
     #[inline]
-    fn create_proof_with_assignment(
+    fn create_proof_with_assignment<M: MsmBackend>(
         pk: &ProvingKey<E>,
         r: E::ScalarField,
         s: E::ScalarField,
         h: &[E::ScalarField],
         input_assignment: &[E::ScalarField],
         aux_assignment: &[E::ScalarField],
-    ) -> R1CSResult<Proof<E>> {
+        msm: &M,
+    ) -> Result<Proof<E>, ProvingError> {
+        Self::create_proof_with_assignment_and_checkpoint(
+            pk,
+            r,
+            s,
+            h,
+            input_assignment,
+            aux_assignment,
+            msm,
+            &mut |_| Ok(()),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_proof_with_assignment_and_checkpoint<M: MsmBackend>(
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        h: &[E::ScalarField],
+        input_assignment: &[E::ScalarField],
+        aux_assignment: &[E::ScalarField],
+        msm: &M,
+        checkpoint: Checkpoint<'_>,
+    ) -> Result<Proof<E>, ProvingError> {
+        // The generator derives `h_query` from the same evaluation domain as `h`, minus the
+        // highest coefficient, which is always zero. A mismatch means the circuit does not
+        // match the key, and the MSM below would silently truncate.
+        check_quotient_length(pk, h)?;
+
+        // Keys generated without `b_g1_query` only support `r = 0`.
+        if !r.is_zero() && pk.b_g1_query.is_empty() {
+            return Err(ProvingError::MissingBG1Query);
+        }
+
         let c_acc_time = start_timer!(|| "Compute C");
         let h_assignment = cfg_into_iter!(h)
             .map(|s| s.into_bigint())
             .collect::<Vec<_>>();
-        let h_acc = E::G1::msm_bigint(&pk.h_query, &h_assignment[..h_assignment.len() - 1]);
-        drop(h_assignment);
+        let h_acc: E::G1 = msm.msm_bigint(&pk.h_query, &h_assignment[..h_assignment.len() - 1]);
+        wipe(h_assignment);
 
         // Compute C
         let aux_assignment = cfg_iter!(aux_assignment)
             .map(|s| s.into_bigint())
             .collect::<Vec<_>>();
 
-        let l_aux_acc = E::G1::msm_bigint(&pk.l_query, &aux_assignment);
+        let l_aux_acc: E::G1 = msm.msm_bigint(&pk.l_query, &aux_assignment);
 
         let r_s_delta_g1 = pk.delta_g1 * (r * s);
 
         end_timer!(c_acc_time);
 
+        if let Err(e) = checkpoint(ProvingStage::CAccumulated) {
+            wipe(aux_assignment);
+            return Err(e);
+        }
+
         let input_assignment = input_assignment
             .iter()
             .map(|s| s.into_bigint())
             .collect::<Vec<_>>();
 
         let assignment = [&input_assignment[..], &aux_assignment[..]].concat();
-        drop(aux_assignment);
+        wipe(aux_assignment);
 
         // Compute A
         let a_acc_time = start_timer!(|| "Compute A");
         let r_g1 = pk.delta_g1.mul(r);
 
-        let g_a = Self::calculate_coeff(r_g1, &pk.a_query, pk.vk.alpha_g1, &assignment);
+        let g_a = Self::calculate_coeff(r_g1, &pk.a_query, pk.vk.alpha_g1, &assignment, msm);
 
         let s_g_a = g_a * &s;
         end_timer!(a_acc_time);
 
+        if let Err(e) = checkpoint(ProvingStage::AComputed) {
+            wipe(assignment);
+            return Err(e);
+        }
+
         // Compute B in G1 if needed
         let g1_b = if !r.is_zero() {
             let b_g1_acc_time = start_timer!(|| "Compute B in G1");
             let s_g1 = pk.delta_g1.mul(s);
-            let g1_b = Self::calculate_coeff(s_g1, &pk.b_g1_query, pk.beta_g1, &assignment);
+            let g1_b = Self::calculate_coeff(s_g1, &pk.b_g1_query, pk.beta_g1, &assignment, msm);
 
             end_timer!(b_g1_acc_time);
 
@@ -110,12 +431,14 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         // Compute B in G2
         let b_g2_acc_time = start_timer!(|| "Compute B in G2");
         let s_g2 = pk.vk.delta_g2.mul(s);
-        let g2_b = Self::calculate_coeff(s_g2, &pk.b_g2_query, pk.vk.beta_g2, &assignment);
+        let g2_b = Self::calculate_coeff(s_g2, &pk.b_g2_query, pk.vk.beta_g2, &assignment, msm);
         let r_g1_b = g1_b * &r;
-        drop(assignment);
+        wipe(assignment);
 
         end_timer!(b_g2_acc_time);
 
+        checkpoint(ProvingStage::BComputed)?;
+
         let c_time = start_timer!(|| "Finish C");
         let mut g_c = s_g_a;
         g_c += &r_g1_b;
@@ -124,6 +447,8 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         g_c += &h_acc;
         end_timer!(c_time);
 
+        checkpoint(ProvingStage::CComputed)?;
+
         Ok(Proof {
             a: g_a.into_affine(),
             b: g2_b.into_affine(),
@@ -139,7 +464,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         circuit: C,
         pk: &ProvingKey<E>,
         rng: &mut impl Rng,
-    ) -> R1CSResult<Proof<E>>
+    ) -> Result<Proof<E>, ProvingError>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
@@ -149,13 +474,39 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         Self::create_proof_with_reduction(circuit, pk, r, s)
     }
 
+    /// Create one Groth16 proof per circuit in `circuits` using the provided
+    /// R1CS-to-QAP reduction. The randomness for the `i`-th proof is sampled
+    /// from a [`ChaCha20Rng`] seeded with `master_seed` and `i`, so the output
+    /// depends only on `master_seed` and not on how the proofs are scheduled
+    /// across threads.
+    pub fn prove_batch_seeded<C>(
+        pk: &ProvingKey<E>,
+        circuits: Vec<C>,
+        master_seed: u64,
+    ) -> Result<Vec<Proof<E>>, ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField> + Send,
+    {
+        cfg_into_iter!(circuits)
+            .enumerate()
+            .map(|(i, circuit)| {
+                let mut seed = [0u8; 32];
+                seed[..8].copy_from_slice(&master_seed.to_le_bytes());
+                seed[8..16].copy_from_slice(&(i as u64).to_le_bytes());
+                let mut rng = ChaCha20Rng::from_seed(seed);
+
+                Self::create_random_proof_with_reduction(circuit, pk, &mut rng)
+            })
+            .collect()
+    }
+
     /// Create a Groth16 proof that is *not* zero-knowledge with the provided
     /// R1CS-to-QAP reduction.
     #[inline]
     pub fn create_proof_with_reduction_no_zk<C>(
         circuit: C,
         pk: &ProvingKey<E>,
-    ) -> R1CSResult<Proof<E>>
+    ) -> Result<Proof<E>, ProvingError>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
@@ -175,17 +526,158 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         pk: &ProvingKey<E>,
         r: E::ScalarField,
         s: E::ScalarField,
-    ) -> R1CSResult<Proof<E>>
+    ) -> Result<Proof<E>, ProvingError>
     where
         E: Pairing,
         C: ConstraintSynthesizer<E::ScalarField>,
         QAP: R1CSToQAP,
     {
+        Self::create_proof_with_reduction_and_msm(circuit, pk, r, s, &ThresholdMsm::default())
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s`, the provided
+    /// R1CS-to-QAP reduction, and the provided MSM backend.
+    #[inline]
+    pub fn create_proof_with_reduction_and_msm<C, M>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        msm: &M,
+    ) -> Result<Proof<E>, ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        M: MsmBackend,
+    {
+        Self::create_proof_with_options(
+            circuit,
+            pk,
+            r,
+            s,
+            OptimizationGoal::Constraints,
+            msm,
+            &mut |_| Ok(()),
+        )
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s`, the provided
+    /// R1CS-to-QAP reduction, and the optimization goal `goal`. This must be the
+    /// goal `pk` was generated with, e.g. by
+    /// [`Self::generate_random_parameters_with_goal`], as recorded in
+    /// `pk.optimization_goal`; otherwise this returns
+    /// [`ProvingError::OptimizationGoalMismatch`].
+    #[inline]
+    pub fn create_proof_with_reduction_and_goal<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        goal: OptimizationGoal,
+    ) -> Result<Proof<E>, ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::create_proof_with_options(
+            circuit,
+            pk,
+            r,
+            s,
+            goal,
+            &ThresholdMsm::default(),
+            &mut |_| Ok(()),
+        )
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, checking `cancel` after each [`ProvingStage`] but
+    /// the last, [`ProvingStage::CComputed`], which completes the proof. Once
+    /// `cancel` is set, this returns [`ProvingError::Cancelled`] at the next
+    /// check, after freeing the prover's copies of the witness, which are also
+    /// wiped with the `zeroize` feature.
+    pub fn create_proof_cancellable<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        cancel: &AtomicBool,
+    ) -> Result<Proof<E>, ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::create_proof_with_options(
+            circuit,
+            pk,
+            r,
+            s,
+            OptimizationGoal::Constraints,
+            &ThresholdMsm::default(),
+            &mut |stage| {
+                if stage != ProvingStage::CComputed && cancel.load(Ordering::Relaxed) {
+                    Err(ProvingError::Cancelled)
+                } else {
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge using the provided
+    /// R1CS-to-QAP reduction, calling `progress` after each [`ProvingStage`], in
+    /// the order in which the stages are declared.
+    /// This method samples randomness for zero knowledges via `rng`.
+    pub fn create_proof_with_progress<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        rng: &mut impl Rng,
+        mut progress: impl FnMut(ProvingStage),
+    ) -> Result<Proof<E>, ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        Self::create_proof_with_options(
+            circuit,
+            pk,
+            r,
+            s,
+            OptimizationGoal::Constraints,
+            &ThresholdMsm::default(),
+            &mut |stage| {
+                progress(stage);
+                Ok(())
+            },
+        )
+    }
+
+    fn create_proof_with_options<C, M>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        goal: OptimizationGoal,
+        msm: &M,
+        checkpoint: Checkpoint<'_>,
+    ) -> Result<Proof<E>, ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        M: MsmBackend,
+    {
+        // Proving with a different goal than setup can silently change the
+        // variable layout.
+        if pk.optimization_goal.0 != goal {
+            return Err(ProvingError::OptimizationGoalMismatch {
+                key: pk.optimization_goal.0,
+                prover: goal,
+            });
+        }
+
         let prover_time = start_timer!(|| "Groth16::Prover");
         let cs = ConstraintSystem::new_ref();
 
         // Set the optimization goal
-        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_optimization_goal(goal);
 
         // Synthesize the circuit.
         let synthesis_time = start_timer!(|| "Constraint synthesis");
@@ -197,25 +689,110 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         cs.finalize();
         end_timer!(lc_time);
 
+        // A circuit or optimization goal that differs from the one used at setup
+        // typically changes the number of variables.
+        check_layout(pk, cs.num_instance_variables(), cs.num_witness_variables())?;
+        QAP::check_domain_size::<E::ScalarField>(
+            cs.num_constraints(),
+            cs.num_instance_variables(),
+        )?;
+
+        checkpoint(ProvingStage::Synthesized)?;
+
         let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
         let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
         end_timer!(witness_map_time);
 
+        if let Err(e) = checkpoint(ProvingStage::WitnessMapped) {
+            wipe(h);
+            return Err(e);
+        }
+
         let prover = cs.borrow().unwrap();
-        let proof = Self::create_proof_with_assignment(
+        let proof = Self::create_proof_with_assignment_and_checkpoint(
             pk,
             r,
             s,
             &h,
             &prover.instance_assignment[1..],
             &prover.witness_assignment,
-        )?;
+            msm,
+            checkpoint,
+        );
+        wipe(h);
+        let proof = proof?;
 
         end_timer!(prover_time);
 
         Ok(proof)
     }
 
+    /// Generate a random common reference string for `circuit` together with a proof for the
+    /// witness it assigns, synthesizing the circuit only once. The generator ignores the
+    /// witness, so the same constraint system serves both setup and proving.
+    pub fn setup_and_prove<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> Result<(ProvingKey<E>, Proof<E>), ProvingError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+
+        let synthesis_time = start_timer!(|| "Constraint synthesis");
+        circuit.generate_constraints(cs.clone())?;
+        debug_assert!(cs.is_satisfied().unwrap());
+        end_timer!(synthesis_time);
+
+        let lc_time = start_timer!(|| "Inlining LCs");
+        cs.finalize();
+        end_timer!(lc_time);
+
+        let pk = Self::generate_random_parameters_from_cs(cs.clone(), false, rng)?;
+
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
+        end_timer!(witness_map_time);
+
+        let prover = cs.borrow().unwrap();
+        let proof = Self::create_proof_with_assignment(
+            &pk,
+            r,
+            s,
+            &h,
+            &prover.instance_assignment[1..],
+            &prover.witness_assignment,
+            &ThresholdMsm::default(),
+        );
+        wipe(h);
+
+        Ok((pk, proof?))
+    }
+
+    /// Commit to the QAP witness polynomial `h` that `QAP` derives from the finalized
+    /// constraint system `cs`, as `sum_i h_i * pk.h_query[i]`. This is the `h` term the
+    /// prover adds to `C`, exposed so that custom `R1CSToQAP` implementations can be
+    /// compared against each other or against a reference while debugging.
+    #[cfg(any(test, feature = "debug-tools"))]
+    pub fn commit_h_poly(
+        pk: &ProvingKey<E>,
+        cs: ark_relations::r1cs::ConstraintSystemRef<E::ScalarField>,
+    ) -> Result<E::G1Affine, ProvingError> {
+        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs)?;
+        check_quotient_length(pk, &h)?;
+
+        let h_assignment = cfg_iter!(h).map(|s| s.into_bigint()).collect::<Vec<_>>();
+        wipe(h);
+        let h_acc = E::G1::msm_bigint(&pk.h_query, &h_assignment[..h_assignment.len() - 1]);
+        wipe(h_assignment);
+
+        Ok(h_acc.into_affine())
+    }
+
     /// Given a Groth16 proof, returns a fresh proof of the same statement. For a proof π of a
     /// statement S, the output of the non-deterministic procedure `rerandomize_proof(π)` is
     /// statistically indistinguishable from a fresh honest proof of S. For more info, see theorem 3 of
@@ -249,17 +826,18 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         }
     }
 
-    fn calculate_coeff<G: AffineRepr>(
+    fn calculate_coeff<G: AffineRepr, M: MsmBackend>(
         initial: G::Group,
         query: &[G],
         vk_param: G,
         assignment: &[<G::ScalarField as PrimeField>::BigInt],
+        msm: &M,
     ) -> G::Group
     where
         G::Group: VariableBaseMSM<MulBase = G>,
     {
         let el = query[0];
-        let acc = G::Group::msm_bigint(&query[1..], assignment);
+        let acc: G::Group = msm.msm_bigint(&query[1..], assignment);
 
         let mut res = initial;
         res.add_assign(&el);
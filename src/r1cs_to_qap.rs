@@ -1,8 +1,8 @@
-use ark_ff::{One, PrimeField, Zero};
+use ark_ff::{FftField, One, PrimeField, Zero};
 use ark_poly::EvaluationDomain;
 use ark_std::{cfg_iter, cfg_iter_mut, vec};
 
-use crate::Vec;
+use crate::{prover::wipe, Vec};
 use ark_relations::r1cs::{
     ConstraintMatrices, ConstraintSystemRef, Result as R1CSResult, SynthesisError,
 };
@@ -11,6 +11,68 @@ use core::ops::{AddAssign, Deref};
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// An error indicating that a circuit is too large for the evaluation domains of
+/// its scalar field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DomainSizeError {
+    /// The required domain size, i.e. the number of constraints plus the number of
+    /// instance variables.
+    pub required: usize,
+    /// The largest domain size supported by the field, see [`max_domain_size`].
+    pub max: usize,
+}
+
+impl core::fmt::Display for DomainSizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "circuit requires a domain of size {}, but the field supports at most {}",
+            self.required, self.max
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DomainSizeError {}
+
+/// Used where the [`R1CSToQAP`] interface requires a [`SynthesisError`]. The
+/// generator and the prover run [`R1CSToQAP::check_domain_size`] first, so they
+/// report the [`DomainSizeError`] itself.
+impl From<DomainSizeError> for SynthesisError {
+    fn from(_: DomainSizeError) -> Self {
+        SynthesisError::PolynomialDegreeTooLarge
+    }
+}
+
+/// The largest size of a [`GeneralEvaluationDomain`](ark_poly::GeneralEvaluationDomain)
+/// over `F`: `2^TWO_ADICITY`, times `SMALL_SUBGROUP_BASE^SMALL_SUBGROUP_BASE_ADICITY` if
+/// `F` supports mixed-radix FFTs. Saturates at `usize::MAX`.
+pub fn max_domain_size<F: FftField>() -> usize {
+    let radix2 = 1usize.checked_shl(F::TWO_ADICITY).unwrap_or(usize::MAX);
+    match (F::SMALL_SUBGROUP_BASE, F::SMALL_SUBGROUP_BASE_ADICITY) {
+        (Some(base), Some(adicity)) => (base as usize)
+            .checked_pow(adicity)
+            .and_then(|small| radix2.checked_mul(small))
+            .unwrap_or(usize::MAX),
+        _ => radix2,
+    }
+}
+
+/// Check that a circuit with `num_constraints` constraints and `num_instance_variables`
+/// instance variables fits in an evaluation domain over `F`, returning the required
+/// domain size. This is the default [`R1CSToQAP::check_domain_size`].
+pub fn check_domain_size<F: FftField>(
+    num_constraints: usize,
+    num_instance_variables: usize,
+) -> Result<usize, DomainSizeError> {
+    let required = num_constraints.saturating_add(num_instance_variables);
+    let max = max_domain_size::<F>();
+    if required > max {
+        return Err(DomainSizeError { required, max });
+    }
+    Ok(required)
+}
+
 #[inline]
 /// Computes the inner product of `terms` with `assignment`.
 pub fn evaluate_constraint<'a, LHS, RHS, R>(terms: &'a [(LHS, usize)], assignment: &'a [RHS]) -> R
@@ -71,12 +133,14 @@ pub trait R1CSToQAP {
         ]
         .concat();
 
-        Self::witness_map_from_matrices::<F, D>(
+        let h = Self::witness_map_from_matrices::<F, D>(
             &matrices,
             num_inputs,
             num_constraints,
             &full_assignment,
-        )
+        );
+        wipe(full_assignment);
+        h
     }
 
     /// Computes a QAP witness corresponding to the R1CS witness defined by `cs`.
@@ -95,6 +159,17 @@ pub trait R1CSToQAP {
         zt: F,
         delta_inverse: F,
     ) -> Result<Vec<F>, SynthesisError>;
+
+    /// Checks that the reduction supports a circuit with `num_constraints`
+    /// constraints and `num_instance_variables` instance variables, returning the
+    /// required domain size. The generator and the prover call this before the
+    /// reduction itself.
+    fn check_domain_size<F: PrimeField>(
+        num_constraints: usize,
+        num_instance_variables: usize,
+    ) -> Result<usize, DomainSizeError> {
+        check_domain_size::<F>(num_constraints, num_instance_variables)
+    }
 }
 
 /// Computes the R1CS-to-QAP reduction defined in [`libsnark`](https://github.com/scipr-lab/libsnark/blob/2af440246fa2c3d0b1b0a425fb6abd8cc8b9c54d/libsnark/reductions/r1cs_to_qap/r1cs_to_qap.tcc).
@@ -108,7 +183,8 @@ impl R1CSToQAP for LibsnarkReduction {
         t: &F,
     ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
         let matrices = cs.to_matrices().unwrap();
-        let domain_size = cs.num_constraints() + cs.num_instance_variables();
+        let domain_size =
+            check_domain_size::<F>(cs.num_constraints(), cs.num_instance_variables())?;
         let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
         let domain_size = domain.size();
 
@@ -153,8 +229,8 @@ impl R1CSToQAP for LibsnarkReduction {
         num_constraints: usize,
         full_assignment: &[F],
     ) -> R1CSResult<Vec<F>> {
-        let domain =
-            D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let domain_size = check_domain_size::<F>(num_constraints, num_inputs)?;
+        let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
         let domain_size = domain.size();
         let zero = F::zero();
 
@@ -185,8 +261,8 @@ impl R1CSToQAP for LibsnarkReduction {
         coset_domain.fft_in_place(&mut b);
 
         let mut ab = domain.mul_polynomials_in_evaluation_domain(&a, &b);
-        drop(a);
-        drop(b);
+        wipe(a);
+        wipe(b);
 
         let mut c = vec![zero; domain_size];
         cfg_iter_mut!(c[..num_constraints])
@@ -202,10 +278,11 @@ impl R1CSToQAP for LibsnarkReduction {
             .evaluate_vanishing_polynomial(F::GENERATOR)
             .inverse()
             .unwrap();
-        cfg_iter_mut!(ab).zip(c).for_each(|(ab_i, c_i)| {
-            *ab_i -= &c_i;
+        cfg_iter_mut!(ab).zip(cfg_iter!(c)).for_each(|(ab_i, c_i)| {
+            *ab_i -= c_i;
             *ab_i *= &vanishing_polynomial_over_coset;
         });
+        wipe(c);
 
         coset_domain.ifft_in_place(&mut ab);
 
@@ -0,0 +1,46 @@
+use super::{seeded_rng, MySillyCircuit};
+use crate::{prepare_verifying_key, VerifyingKey};
+use ark_ec::pairing::Pairing;
+use ark_std::UniformRand;
+
+fn test_compat_api<E>()
+where
+    E: Pairing,
+{
+    use crate::compat::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+
+    let mut rng = seeded_rng();
+
+    let pk = generate_random_parameters::<E, _, _>(MySillyCircuit { a: None, b: None }, &mut rng)
+        .unwrap();
+    let pvk = prepare_verifying_key(&pk.vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = create_random_proof(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &pk,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(verify_proof(&pvk, &proof, &[a * b]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[a]).unwrap());
+
+    // Upstream does not reject default keys when preparing them.
+    let _ = prepare_verifying_key::<E>(&VerifyingKey::default());
+}
+
+mod bls12_377 {
+    use super::test_compat_api;
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn compat_api() {
+        test_compat_api::<Bls12_377>();
+    }
+}
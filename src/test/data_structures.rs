@@ -0,0 +1,616 @@
+use super::{prove_silly, seeded_rng, ManyInputsCircuit, MySillyCircuit, SillyProof};
+use crate::{
+    prepare_verifying_key, prover::ProvingError, CompactVerifyingKey, Groth16, InputError,
+    PreparedInputs, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey, KEY_FORMAT_VERSION,
+};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{One, PrimeField, Zero};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use ark_std::{test_rng, UniformRand};
+
+fn test_deserialize_versioned_key<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+
+    // A version 1 blob is the version byte followed by the canonical encoding,
+    // which version 2 left unchanged for verification keys.
+    let mut blob = vec![1u8];
+    vk.serialize_compressed(&mut blob).unwrap();
+
+    let mut versioned = Vec::new();
+    vk.serialize_versioned(&mut versioned, Compress::Yes)
+        .unwrap();
+    assert_eq!(KEY_FORMAT_VERSION, 2);
+    assert_eq!(blob[1..], versioned[1..]);
+
+    let migrated =
+        VerifyingKey::<E>::deserialize_any_version(&blob[..], Compress::Yes, Validate::Yes)
+            .unwrap();
+    assert_eq!(migrated, vk);
+
+    assert!(Groth16::<E>::verify(&migrated, &[a * b], &proof).unwrap());
+
+    // Unknown versions are rejected.
+    blob[0] = 0xff;
+    assert!(
+        VerifyingKey::<E>::deserialize_any_version(&blob[..], Compress::Yes, Validate::Yes)
+            .is_err()
+    );
+
+    // Unversioned keys, such as those written by earlier releases, are read with
+    // `CanonicalDeserialize`, since their first byte is not a version.
+    let unversioned = &versioned[1..];
+    assert_eq!(
+        VerifyingKey::<E>::deserialize_compressed(unversioned).unwrap(),
+        vk
+    );
+    assert_ne!(
+        VerifyingKey::<E>::deserialize_any_version(unversioned, Compress::Yes, Validate::Yes).ok(),
+        Some(vk)
+    );
+}
+
+fn test_refresh_prepared_verifying_key<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (_, vk1) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (pk2, vk2) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let (proof, a, b) = prove_silly(&pk2, &mut rng);
+
+    let mut pvk = prepare_verifying_key::<E>(&vk1).unwrap();
+    pvk.vk = vk2.clone();
+    pvk.refresh();
+
+    assert_eq!(pvk, prepare_verifying_key::<E>(&vk2).unwrap());
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+}
+
+fn test_describe_keys<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    // `MySillyCircuit` has two instance variables (including the constant one),
+    // two witness variables, and six constraints, giving an evaluation domain of
+    // size eight.
+    let description = pk.describe();
+    assert_eq!(description.vk, vk.describe());
+    assert_eq!(description.vk.num_instance_variables, 2);
+    assert_eq!(
+        description.vk.g1_size,
+        E::G1Affine::generator().compressed_size() as u64
+    );
+    assert_eq!(
+        description.vk.g2_size,
+        E::G2Affine::generator().compressed_size() as u64
+    );
+    assert_eq!(description.a_query_len, 4);
+    assert_eq!(description.b_g1_query_len, 4);
+    assert_eq!(description.b_g2_query_len, 4);
+    assert_eq!(description.h_query_len, 7);
+    assert_eq!(description.l_query_len, 2);
+}
+
+fn test_prepared_verifying_key_from_parts<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let prepared = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let pvk = PreparedVerifyingKey::from_parts(
+        vk.clone(),
+        prepared.alpha_g1_beta_g2,
+        prepared.gamma_g2_neg_pc.clone(),
+        prepared.delta_g2_neg_pc.clone(),
+    );
+    assert!(pvk.validate());
+
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    let inconsistent = PreparedVerifyingKey::from_parts(
+        vk,
+        prepared.alpha_g1_beta_g2,
+        prepared.delta_g2_neg_pc,
+        prepared.gamma_g2_neg_pc,
+    );
+    assert!(!inconsistent.validate());
+}
+
+fn test_check_crs_consistency<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert!(vk.check_crs_consistency(&pk).unwrap());
+
+    let mut tampered = pk.clone();
+    tampered.b_g2_query.swap(0, 3);
+    assert!(!vk.check_crs_consistency(&tampered).unwrap());
+
+    let mut tampered = pk.clone();
+    tampered.delta_g1 = (tampered.delta_g1 + tampered.beta_g1).into();
+    assert!(!vk.check_crs_consistency(&tampered).unwrap());
+
+    let (other_pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert!(!vk.check_crs_consistency(&other_pk).unwrap());
+}
+
+fn test_serialize_proof_network_order<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+
+    let mut network = Vec::new();
+    proof.serialize_network(&mut network).unwrap();
+    assert_eq!(network.len(), proof.compressed_size());
+
+    // Each element is the byte-reversal of its canonical encoding.
+    let mut offset = 0;
+    for element in [
+        proof.a.serialize_compressed_to_vec(),
+        proof.b.serialize_compressed_to_vec(),
+        proof.c.serialize_compressed_to_vec(),
+    ] {
+        let mut reversed = network[offset..offset + element.len()].to_vec();
+        reversed.reverse();
+        assert_eq!(reversed, element);
+        offset += element.len();
+    }
+
+    let decoded = Proof::<E>::deserialize_network(&network[..]).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &decoded).unwrap());
+
+    // The canonical decoder does not accept the network encoding as-is.
+    assert_ne!(
+        Proof::<E>::deserialize_compressed(&network[..]).ok(),
+        Some(proof)
+    );
+}
+
+fn test_partition_assignment<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let cs = ConstraintSystem::new_ref();
+    MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    let cs = cs.into_inner().unwrap();
+    let full = [cs.instance_assignment, cs.witness_assignment].concat();
+
+    let (instance, witness) = vk.partition_assignment(&full);
+    assert_eq!(instance, &[E::ScalarField::one(), a * b]);
+    assert_eq!(witness, &[a, b]);
+}
+
+fn test_compact_verifying_key<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+    let cvk = CompactVerifyingKey::from(&pvk);
+    assert_eq!(cvk.gamma_abc_g1().collect::<Vec<_>>(), vk.gamma_abc_g1);
+
+    for inputs in [vec![a * b], vec![a]] {
+        assert_eq!(
+            Groth16::<E>::verify_proof_compact(&cvk, &proof, &inputs).unwrap(),
+            Groth16::<E>::verify_proof(&pvk, &proof, &inputs).unwrap()
+        );
+    }
+    assert!(Groth16::<E>::verify_proof_compact(&cvk, &proof, &[]).is_err());
+}
+
+fn test_verifying_key_fingerprint<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let mut bytes = Vec::new();
+    vk.serialize_compressed(&mut bytes).unwrap();
+    let decoded = VerifyingKey::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(decoded.fingerprint(), vk.fingerprint());
+
+    let mut tampered = vk.clone();
+    tampered.gamma_abc_g1[0] = (tampered.gamma_abc_g1[0] + tampered.alpha_g1).into();
+    assert_ne!(tampered.fingerprint(), vk.fingerprint());
+}
+
+fn test_optimization_goal_mismatch<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert_eq!(pk.optimization_goal.0, OptimizationGoal::Constraints);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let result = Groth16::<E>::create_proof_with_reduction_and_goal(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &pk,
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        OptimizationGoal::Weight,
+    );
+    assert_eq!(
+        result.err(),
+        Some(ProvingError::OptimizationGoalMismatch {
+            key: OptimizationGoal::Constraints,
+            prover: OptimizationGoal::Weight,
+        })
+    );
+
+    // The goal survives serialization, so a key set up for a different goal is
+    // still rejected after a round trip.
+    let weight_pk = Groth16::<E>::generate_random_parameters_with_goal(
+        MySillyCircuit { a: None, b: None },
+        OptimizationGoal::Weight,
+        &mut rng,
+    )
+    .unwrap();
+    let mut canonical = Vec::new();
+    weight_pk.serialize_compressed(&mut canonical).unwrap();
+    assert_eq!(weight_pk.compressed_size(), canonical.len());
+    let decoded = ProvingKey::<E>::deserialize_compressed(&canonical[..]).unwrap();
+    assert_eq!(decoded, weight_pk);
+    assert_eq!(
+        Groth16::<E>::create_proof_with_reduction_and_goal(
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &decoded,
+            E::ScalarField::rand(&mut rng),
+            E::ScalarField::rand(&mut rng),
+            OptimizationGoal::Constraints,
+        )
+        .err(),
+        Some(ProvingError::OptimizationGoalMismatch {
+            key: OptimizationGoal::Weight,
+            prover: OptimizationGoal::Constraints,
+        })
+    );
+
+    let mut versioned = Vec::new();
+    weight_pk
+        .serialize_versioned(&mut versioned, Compress::Yes)
+        .unwrap();
+    assert_eq!(versioned[0], KEY_FORMAT_VERSION);
+    assert_eq!(versioned[1..], canonical[..]);
+    let decoded =
+        ProvingKey::<E>::deserialize_any_version(&versioned[..], Compress::Yes, Validate::Yes)
+            .unwrap();
+    assert_eq!(decoded, weight_pk);
+
+    // Keys in the layout from before the goal was recorded cannot be read as
+    // canonical or version 1 keys without stating the goal.
+    let mut legacy = Vec::new();
+    weight_pk.vk.serialize_compressed(&mut legacy).unwrap();
+    weight_pk.beta_g1.serialize_compressed(&mut legacy).unwrap();
+    weight_pk
+        .delta_g1
+        .serialize_compressed(&mut legacy)
+        .unwrap();
+    weight_pk.a_query.serialize_compressed(&mut legacy).unwrap();
+    weight_pk
+        .b_g1_query
+        .serialize_compressed(&mut legacy)
+        .unwrap();
+    weight_pk
+        .b_g2_query
+        .serialize_compressed(&mut legacy)
+        .unwrap();
+    weight_pk.h_query.serialize_compressed(&mut legacy).unwrap();
+    weight_pk.l_query.serialize_compressed(&mut legacy).unwrap();
+    assert!(ProvingKey::<E>::deserialize_compressed(&legacy[..]).is_err());
+
+    let v1 = [&[1u8][..], &legacy].concat();
+    assert!(
+        ProvingKey::<E>::deserialize_any_version(&v1[..], Compress::Yes, Validate::Yes).is_err()
+    );
+
+    let migrated = ProvingKey::<E>::deserialize_legacy(
+        &v1[1..],
+        OptimizationGoal::Weight,
+        Compress::Yes,
+        Validate::Yes,
+    )
+    .unwrap();
+    assert_eq!(migrated, weight_pk);
+    let migrated = ProvingKey::<E>::deserialize_legacy(
+        &legacy[..],
+        OptimizationGoal::Weight,
+        Compress::Yes,
+        Validate::Yes,
+    )
+    .unwrap();
+    assert_eq!(migrated, weight_pk);
+}
+
+fn test_constant_term<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+    let constant_term = vk.constant_term().unwrap();
+    assert_eq!(constant_term, vk.gamma_abc_g1[0]);
+
+    // With all public inputs zero, only the constant term remains.
+    let prepared_inputs = Groth16::<E>::prepare_inputs(&pvk, &[E::ScalarField::zero()]).unwrap();
+    assert_eq!(prepared_inputs.into_affine(), constant_term);
+
+    let input = E::ScalarField::rand(&mut rng);
+    let prepared_inputs = Groth16::<E>::prepare_inputs(&pvk, &[input]).unwrap();
+    assert_eq!(prepared_inputs, constant_term + vk.gamma_abc_g1[1] * input);
+
+    // A key without the constant term is reported rather than indexed.
+    let mut empty = pvk;
+    empty.vk.gamma_abc_g1.clear();
+    assert_eq!(
+        empty.vk.constant_term(),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+    assert_eq!(
+        PreparedInputs::with_fixed(&empty, &[], &[]),
+        Err(InputError::Synthesis(SynthesisError::MalformedVerifyingKey))
+    );
+    assert_eq!(
+        Groth16::<E>::verify_proof_sparse_inputs(&empty, &Proof::default(), &[]),
+        Err(InputError::Synthesis(SynthesisError::MalformedVerifyingKey))
+    );
+}
+
+fn test_prepared_inputs_with_fixed<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(
+        ManyInputsCircuit::<E::ScalarField> {
+            inputs: vec![None; 6],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let inputs = (0..6)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = ManyInputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let fixed_indices = [4, 1, 2];
+    let fixed_values = fixed_indices.map(|i| inputs[i]);
+    let prepared = PreparedInputs::with_fixed(&pvk, &fixed_indices, &fixed_values).unwrap();
+    assert_eq!(prepared.num_volatile(), 3);
+
+    let volatile_values = [inputs[0], inputs[3], inputs[5]];
+    let prepared_inputs = prepared.update_volatile(&volatile_values).unwrap();
+    assert_eq!(
+        prepared_inputs,
+        Groth16::<E>::prepare_inputs(&pvk, &inputs).unwrap()
+    );
+    assert!(
+        Groth16::<E>::verify_proof_with_prepared_inputs(&pvk, &proof, &prepared_inputs).unwrap()
+    );
+
+    let a = inputs[0];
+    assert_eq!(
+        prepared.update_volatile(&volatile_values[1..]).err(),
+        Some(InputError::LengthMismatch {
+            expected: 3,
+            actual: 2,
+        })
+    );
+    assert_eq!(
+        PreparedInputs::with_fixed(&pvk, &[6], &[a]).err(),
+        Some(InputError::IndexOutOfRange {
+            index: 6,
+            num_inputs: 6,
+        })
+    );
+    assert_eq!(
+        PreparedInputs::with_fixed(&pvk, &[1, 1], &[a, a]).err(),
+        Some(InputError::DuplicateIndex(1))
+    );
+    assert_eq!(
+        PreparedInputs::with_fixed(&pvk, &[1], &[]).err(),
+        Some(InputError::LengthMismatch {
+            expected: 1,
+            actual: 0,
+        })
+    );
+}
+
+fn test_public_inputs_as_field_elements<E, F>()
+where
+    E: Pairing,
+    F: PrimeField,
+{
+    let mut rng = test_rng();
+
+    let mut inputs = (0..4)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    inputs.push(E::ScalarField::zero());
+    inputs.push(-E::ScalarField::one());
+
+    for outer_field_bits in [1, 64, F::MODULUS_BIT_SIZE as usize - 1] {
+        let limbs = Groth16::<E>::public_inputs_as_field_elements::<F>(&inputs, outer_field_bits);
+        let limbs_per_input =
+            (E::ScalarField::MODULUS_BIT_SIZE as usize + outer_field_bits - 1) / outer_field_bits;
+        assert_eq!(limbs.len(), inputs.len() * limbs_per_input);
+        assert_eq!(
+            Groth16::<E>::public_inputs_from_field_elements(&limbs, outer_field_bits),
+            Some(inputs.clone())
+        );
+
+        let mut oversized = limbs.clone();
+        oversized[0] = F::from(2u8).pow([outer_field_bits as u64]);
+        assert_eq!(
+            Groth16::<E>::public_inputs_from_field_elements(&oversized, outer_field_bits),
+            None
+        );
+    }
+}
+
+mod bls12_377 {
+    use super::{
+        test_check_crs_consistency, test_compact_verifying_key, test_constant_term,
+        test_describe_keys, test_deserialize_versioned_key, test_optimization_goal_mismatch,
+        test_partition_assignment, test_prepared_inputs_with_fixed,
+        test_prepared_verifying_key_from_parts, test_public_inputs_as_field_elements,
+        test_refresh_prepared_verifying_key, test_serialize_proof_network_order,
+        test_verifying_key_fingerprint,
+    };
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn deserialize_versioned_key() {
+        test_deserialize_versioned_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn refresh_prepared_verifying_key() {
+        test_refresh_prepared_verifying_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn describe_keys() {
+        test_describe_keys::<Bls12_377>();
+    }
+
+    #[test]
+    fn prepared_verifying_key_from_parts() {
+        test_prepared_verifying_key_from_parts::<Bls12_377>();
+    }
+
+    #[test]
+    fn check_crs_consistency() {
+        test_check_crs_consistency::<Bls12_377>();
+    }
+
+    #[test]
+    fn serialize_proof_network_order() {
+        test_serialize_proof_network_order::<Bls12_377>();
+    }
+
+    #[test]
+    fn partition_assignment() {
+        test_partition_assignment::<Bls12_377>();
+    }
+
+    #[test]
+    fn compact_verifying_key() {
+        test_compact_verifying_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_fingerprint() {
+        test_verifying_key_fingerprint::<Bls12_377>();
+    }
+
+    #[test]
+    fn optimization_goal_mismatch() {
+        test_optimization_goal_mismatch::<Bls12_377>();
+    }
+
+    #[test]
+    fn constant_term() {
+        test_constant_term::<Bls12_377>();
+    }
+
+    #[test]
+    fn prepared_inputs_with_fixed() {
+        test_prepared_inputs_with_fixed::<Bls12_377>();
+    }
+
+    #[test]
+    fn public_inputs_as_field_elements() {
+        test_public_inputs_as_field_elements::<Bls12_377, ark_bls12_377::Fq>();
+    }
+}
+
+mod bw6_761 {
+    use super::{
+        test_compact_verifying_key, test_deserialize_versioned_key,
+        test_optimization_goal_mismatch, test_prepared_inputs_with_fixed,
+        test_serialize_proof_network_order,
+    };
+    use ark_bw6_761::BW6_761;
+
+    #[test]
+    fn deserialize_versioned_key() {
+        test_deserialize_versioned_key::<BW6_761>();
+    }
+
+    #[test]
+    fn serialize_proof_network_order() {
+        test_serialize_proof_network_order::<BW6_761>();
+    }
+
+    #[test]
+    fn compact_verifying_key() {
+        test_compact_verifying_key::<BW6_761>();
+    }
+
+    #[test]
+    fn optimization_goal_mismatch() {
+        test_optimization_goal_mismatch::<BW6_761>();
+    }
+
+    #[test]
+    fn prepared_inputs_with_fixed() {
+        test_prepared_inputs_with_fixed::<BW6_761>();
+    }
+}
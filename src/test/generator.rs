@@ -0,0 +1,249 @@
+use super::{prove_silly, seeded_rng, ManyConstraintsCircuit, MySillyCircuit};
+use crate::{
+    generator::{SetupError, ToxicWasteError},
+    Groth16,
+};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{One, Zero};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal, SynthesisError,
+    SynthesisMode,
+};
+use ark_std::{
+    rand::{rngs::StdRng, SeedableRng},
+    UniformRand,
+};
+
+fn test_zero_toxic_waste<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+    let one = E::ScalarField::one();
+    let zero = E::ScalarField::zero();
+
+    assert_eq!(
+        Groth16::<E>::check_toxic_waste(zero, one),
+        Err(ToxicWasteError::ZeroGamma)
+    );
+    assert_eq!(
+        Groth16::<E>::check_toxic_waste(one, zero),
+        Err(ToxicWasteError::ZeroDelta)
+    );
+    assert_eq!(Groth16::<E>::check_toxic_waste(one, one), Ok(()));
+
+    let result = Groth16::<E>::generate_parameters_with_qap(
+        MySillyCircuit { a: None, b: None },
+        one,
+        one,
+        zero,
+        one,
+        E::G1Affine::generator().into_group(),
+        E::G2Affine::generator().into_group(),
+        &mut rng,
+    );
+    assert_eq!(
+        result.err(),
+        Some(SetupError::ToxicWaste(ToxicWasteError::ZeroGamma))
+    );
+}
+
+fn test_setup_without_constant<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let result = Groth16::<E>::generate_random_parameters_from_cs(
+        ConstraintSystemRef::None,
+        false,
+        &mut rng,
+    );
+    assert_eq!(result.err(), Some(SetupError::MissingConstant));
+    assert_eq!(
+        SynthesisError::from(SetupError::MissingConstant),
+        SynthesisError::MalformedVerifyingKey
+    );
+}
+
+fn test_query_densities<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    MySillyCircuit { a: None, b: None }
+        .generate_constraints(cs.clone())
+        .unwrap();
+    cs.finalize();
+
+    // The generator maps zero query entries to the identity, and skips the last variable
+    // when counting densities.
+    let qap_num_variables = pk.a_query.len() - 1;
+    let non_zero_a = pk.a_query[..qap_num_variables]
+        .iter()
+        .filter(|p| !p.is_zero())
+        .count();
+    let non_zero_b = pk.b_g2_query[..qap_num_variables]
+        .iter()
+        .filter(|p| !p.is_zero())
+        .count();
+    assert_eq!(
+        Groth16::<E>::query_densities(cs),
+        Ok((non_zero_a, non_zero_b))
+    );
+    assert_eq!(
+        Groth16::<E>::query_densities(ConstraintSystemRef::None),
+        Err(SynthesisError::MissingCS)
+    );
+}
+
+fn test_nums_generators<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let domain = b"MySillyCircuit v1";
+    assert_eq!(
+        Groth16::<E>::nums_generators(domain),
+        Groth16::<E>::nums_generators(domain)
+    );
+    assert_ne!(
+        Groth16::<E>::nums_generators(domain),
+        Groth16::<E>::nums_generators(b"MySillyCircuit v2")
+    );
+
+    let pk = Groth16::<E>::generate_parameters_nums_generators(
+        MySillyCircuit { a: None, b: None },
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        domain,
+        &mut rng,
+    )
+    .unwrap();
+
+    let (proof, a, b) = prove_silly(&pk, &mut rng);
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+}
+
+fn test_g1_table_size_overflow<E>()
+where
+    E: Pairing,
+{
+    assert_eq!(Groth16::<E>::g1_table_size(1, 2, 3, 4), Ok(11));
+    assert_eq!(
+        Groth16::<E>::g1_table_size(usize::MAX / 2, usize::MAX / 2, 0, 0),
+        Ok(usize::MAX)
+    );
+    assert_eq!(
+        Groth16::<E>::g1_table_size(usize::MAX / 2, usize::MAX / 2, 1, 0),
+        Err(SynthesisError::PolynomialDegreeTooLarge)
+    );
+    assert_eq!(
+        Groth16::<E>::g1_table_size(0, 0, 0, usize::MAX),
+        Err(SynthesisError::PolynomialDegreeTooLarge)
+    );
+}
+
+fn test_setup_single_threaded<E>()
+where
+    E: Pairing,
+{
+    let circuit = || ManyConstraintsCircuit::<E::ScalarField> {
+        a: None,
+        b: None,
+        num_constraints: 32,
+    };
+    let setup = || {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(42);
+        Groth16::<E>::setup(circuit(), &mut rng).unwrap().0
+    };
+    let pk = setup();
+    assert_eq!(pk, setup());
+
+    #[cfg(feature = "parallel")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        assert_eq!(pk, pool.install(setup));
+    }
+}
+
+mod bls12_377 {
+    use super::{
+        test_g1_table_size_overflow, test_nums_generators, test_query_densities,
+        test_setup_single_threaded, test_setup_without_constant, test_zero_toxic_waste,
+    };
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn zero_toxic_waste() {
+        test_zero_toxic_waste::<Bls12_377>();
+    }
+
+    #[test]
+    fn query_densities() {
+        test_query_densities::<Bls12_377>();
+    }
+
+    #[test]
+    fn nums_generators() {
+        test_nums_generators::<Bls12_377>();
+    }
+
+    #[test]
+    fn g1_table_size_overflow() {
+        test_g1_table_size_overflow::<Bls12_377>();
+    }
+
+    #[test]
+    fn setup_single_threaded() {
+        test_setup_single_threaded::<Bls12_377>();
+    }
+
+    #[test]
+    fn nums_generators_known_answer() {
+        use ark_bls12_377::{Fq2, G1Affine, G2Affine};
+        use ark_ec::CurveGroup;
+        use ark_ff::MontFp;
+
+        // Generators published for a domain must not change across releases.
+        let (g1, g2) = super::Groth16::<Bls12_377>::nums_generators(b"MySillyCircuit v1");
+        assert_eq!(
+            g1.into_affine(),
+            G1Affine::new(
+                MontFp!("174608292304444088599444420349056118695963178924386567939682418057702830577570260996705114688684490439088264038259"),
+                MontFp!("95146893700417679689955086568940412503081183894969447563750781408756201708363952028735269827202611069938974378604"),
+            )
+        );
+        assert_eq!(
+            g2.into_affine(),
+            G2Affine::new(
+                Fq2::new(
+                    MontFp!("166345332184251841216733067474381389688880576304638161454686062485407541923155520749802445952265306930961874250040"),
+                    MontFp!("76150332805085356293103262477524488130735625752624246771924157378309000144887814308839769046061319822128584400460"),
+                ),
+                Fq2::new(
+                    MontFp!("69324208395819302792905465858300731054479818878569767698491161321747327698457338859566532459535556466181933380225"),
+                    MontFp!("30945935679706705934038992808967690878310484529948394122710639729637913261921250981953873712280593579183659963040"),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn setup_without_constant() {
+        test_setup_without_constant::<Bls12_377>();
+    }
+}
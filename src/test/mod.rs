@@ -0,0 +1,326 @@
+use crate::{prepare_verifying_key, Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{Field, Zero};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use rand_chacha::ChaCha20Rng;
+
+mod compat;
+mod data_structures;
+mod generator;
+mod mpc;
+mod msm;
+mod prover;
+mod r1cs_to_qap;
+mod verifier;
+
+struct MySillyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MySillyCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+            a *= &b;
+            Ok(a)
+        })?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+
+        Ok(())
+    }
+}
+
+struct NoInputCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for NoInputCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_witness_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a * b)
+        })?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+
+        Ok(())
+    }
+}
+
+struct ManyConstraintsCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    num_constraints: usize,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF>
+    for ManyConstraintsCircuit<ConstraintF>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a * b)
+        })?;
+
+        for _ in 0..self.num_constraints {
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A circuit with arbitrary public inputs, whose sum is enforced to equal a
+/// witness variable.
+struct ManyInputsCircuit<F: Field> {
+    inputs: Vec<Option<F>>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for ManyInputsCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let mut sum = lc!();
+        for input in &self.inputs {
+            sum = sum + cs.new_input_variable(|| input.ok_or(SynthesisError::AssignmentMissing))?;
+        }
+        let s = cs.new_witness_variable(|| {
+            self.inputs
+                .iter()
+                .try_fold(ConstraintF::zero(), |acc, input| {
+                    input.map(|input| acc + input)
+                })
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce_constraint(sum, lc!() + Variable::One, lc!() + s)?;
+
+        Ok(())
+    }
+}
+
+/// Generate parameters from fixed toxic waste, fixed generators and a fixed
+/// [`ChaCha20Rng`] seed, so that the result is fully reproducible when debugging.
+fn generate_parameters_deterministic<E, C>(circuit: C) -> ProvingKey<E>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+{
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    Groth16::<E>::generate_parameters_with_qap(
+        circuit,
+        E::ScalarField::from(2u64),
+        E::ScalarField::from(3u64),
+        E::ScalarField::from(5u64),
+        E::ScalarField::from(7u64),
+        E::G1Affine::generator().into_group(),
+        E::G2Affine::generator().into_group(),
+        &mut rng,
+    )
+    .unwrap()
+}
+
+/// Create a proof with fixed randomness `r` and `s`.
+fn create_proof_deterministic<E, C>(circuit: C, pk: &ProvingKey<E>) -> Proof<E>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+{
+    Groth16::<E>::create_proof_with_reduction(
+        circuit,
+        pk,
+        E::ScalarField::from(11u64),
+        E::ScalarField::from(13u64),
+    )
+    .unwrap()
+}
+
+/// An RNG seeded from [`test_rng`].
+fn seeded_rng() -> StdRng {
+    StdRng::seed_from_u64(test_rng().next_u64())
+}
+
+/// Prove `MySillyCircuit` under `pk` for random `a` and `b`, returning the proof,
+/// `a` and `b`.
+fn prove_silly<E: Pairing>(
+    pk: &ProvingKey<E>,
+    rng: &mut StdRng,
+) -> (Proof<E>, E::ScalarField, E::ScalarField) {
+    let a = E::ScalarField::rand(rng);
+    let b = E::ScalarField::rand(rng);
+    let proof = Groth16::<E>::prove(
+        pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        rng,
+    )
+    .unwrap();
+    (proof, a, b)
+}
+
+/// A verifying key for `MySillyCircuit` and a proof for random `a` and `b` under
+/// it, along with the RNG they were sampled from.
+struct SillyProof<E: Pairing> {
+    rng: StdRng,
+    vk: VerifyingKey<E>,
+    a: E::ScalarField,
+    b: E::ScalarField,
+    proof: Proof<E>,
+}
+
+impl<E: Pairing> SillyProof<E> {
+    fn new() -> Self {
+        let mut rng = seeded_rng();
+        let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+        let (proof, a, b) = prove_silly(&pk, &mut rng);
+        Self {
+            rng,
+            vk,
+            a,
+            b,
+            proof,
+        }
+    }
+}
+
+fn test_prove_and_verify<E>(n_iters: usize)
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    for _ in 0..n_iters {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let mut c = a;
+        c *= b;
+
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[c], &proof).unwrap());
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap());
+    }
+}
+
+fn test_rerandomize<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    for _ in 0..10 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let mut c = a;
+        c *= b;
+
+        let proof1 = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        // Rerandomize the proof, then rerandomize that
+        let proof2 = Groth16::<E>::rerandomize_proof(&vk, &proof1, &mut rng);
+        let proof3 = Groth16::<E>::rerandomize_proof(&vk, &proof2, &mut rng);
+
+        // Check correctness: a rerandomized proof validates when the original validates
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[c], &proof1).unwrap());
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[c], &proof2).unwrap());
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[c], &proof3).unwrap());
+
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof1).unwrap());
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof2).unwrap());
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof3).unwrap());
+
+        // Check that the proofs are not equal as group elements
+        assert!(proof1 != proof2);
+        assert!(proof1 != proof3);
+        assert!(proof2 != proof3);
+    }
+}
+
+mod bls12_377 {
+    use super::{test_prove_and_verify, test_rerandomize};
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bls12_377>(100);
+    }
+
+    #[test]
+    fn rerandomize() {
+        test_rerandomize::<Bls12_377>();
+    }
+}
+
+mod bw6_761 {
+    use super::{test_prove_and_verify, test_rerandomize};
+    use ark_bw6_761::BW6_761;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<BW6_761>(1);
+    }
+
+    #[test]
+    fn rerandomize() {
+        test_rerandomize::<BW6_761>();
+    }
+}
@@ -0,0 +1,78 @@
+use super::{prove_silly, seeded_rng, MySillyCircuit};
+use crate::Groth16;
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::pairing::Pairing;
+
+fn test_mpc_contributions<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk0, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (pk1, proof1) = Groth16::<E>::contribute(&pk0, &mut rng).unwrap();
+    let (pk2, proof2) = Groth16::<E>::contribute(&pk1, &mut rng).unwrap();
+
+    assert!(Groth16::<E>::verify_contribution(
+        &pk0, &pk1, &proof1, &mut rng
+    ));
+    assert!(Groth16::<E>::verify_contribution(
+        &pk1, &pk2, &proof2, &mut rng
+    ));
+    assert!(!Groth16::<E>::verify_contribution(
+        &pk0, &pk2, &proof2, &mut rng
+    ));
+    assert!(!Groth16::<E>::verify_contribution(
+        &pk1, &pk2, &proof1, &mut rng
+    ));
+
+    // A contributor that does not update the L query consistently is caught.
+    let mut tampered = pk2.clone();
+    tampered.l_query[0] = pk1.l_query[0];
+    assert!(!Groth16::<E>::verify_contribution(
+        &pk1, &tampered, &proof2, &mut rng
+    ));
+
+    let (proof, a, b) = prove_silly(&pk2, &mut rng);
+    assert!(Groth16::<E>::verify(&pk2.vk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&pk0.vk, &[a * b], &proof).unwrap());
+}
+
+mod bls12_377 {
+    use super::test_mpc_contributions;
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn mpc_contributions() {
+        test_mpc_contributions::<Bls12_377>();
+    }
+
+    #[test]
+    fn transcript_g2_known_answer() {
+        use crate::mpc::transcript_g2;
+        use ark_bls12_377::{Fq2, Fr, G1Affine, G2Affine};
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_ff::MontFp;
+
+        // Contributions are verified by recomputing this point, so it must not
+        // change across releases.
+        let g = G1Affine::generator();
+        assert_eq!(
+            transcript_g2::<Bls12_377>(
+                &g,
+                &(g * Fr::from(2u64)).into_affine(),
+                &(g * Fr::from(3u64)).into_affine(),
+            ),
+            G2Affine::new(
+                Fq2::new(
+                    MontFp!("193467129225650202784071027144888936901856882318066895505923685368971189274577854477923897736144630385437179214225"),
+                    MontFp!("258071663941655815386238769590427596593403210255068517664319513560250167162177680462372628848798608947147244403045"),
+                ),
+                Fq2::new(
+                    MontFp!("87570302016020728019172293106304213763478409955934137925925875190988757930853552093016638654560066521503017390571"),
+                    MontFp!("93105182797804073720340698856232182766030644906734261175987188808962025124795274050827956691848639633444610053645"),
+                ),
+            )
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use super::{seeded_rng, MySillyCircuit};
+use crate::{
+    msm::{ArkworksMsm, MsmBackend, ThresholdMsm},
+    prepare_verifying_key, Groth16,
+};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, VariableBaseMSM};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+
+/// An MSM backend that counts how often it is invoked.
+#[derive(Default)]
+struct CountingMsm {
+    calls: core::cell::Cell<usize>,
+}
+
+impl MsmBackend for CountingMsm {
+    fn msm_bigint<G: VariableBaseMSM>(
+        &self,
+        bases: &[G::MulBase],
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G {
+        self.calls.set(self.calls.get() + 1);
+        ArkworksMsm.msm_bigint(bases, scalars)
+    }
+}
+
+fn test_custom_msm_backend<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let backend = CountingMsm::default();
+    let proof =
+        Groth16::<E>::create_proof_with_reduction_and_msm(circuit(), &pk, r, s, &backend).unwrap();
+    // One MSM each for H, L, A, B in G1, and B in G2.
+    assert_eq!(backend.calls.get(), 5);
+
+    let expected = Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).unwrap();
+    assert_eq!(proof, expected);
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+}
+
+fn test_threshold_msm<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let expected =
+        Groth16::<E>::create_proof_with_reduction_and_msm(circuit(), &pk, r, s, &ArkworksMsm)
+            .unwrap();
+    for serial_threshold in [0, 1, 4, usize::MAX] {
+        let backend = ThresholdMsm { serial_threshold };
+        let proof =
+            Groth16::<E>::create_proof_with_reduction_and_msm(circuit(), &pk, r, s, &backend)
+                .unwrap();
+        assert_eq!(proof, expected);
+    }
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &expected).unwrap());
+}
+
+mod bls12_377 {
+    use super::{test_custom_msm_backend, test_threshold_msm};
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn custom_msm_backend() {
+        test_custom_msm_backend::<Bls12_377>();
+    }
+
+    #[test]
+    fn threshold_msm() {
+        test_threshold_msm::<Bls12_377>();
+    }
+}
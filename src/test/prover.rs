@@ -0,0 +1,852 @@
+use super::{
+    create_proof_deterministic, generate_parameters_deterministic, seeded_rng,
+    ManyConstraintsCircuit, MySillyCircuit, NoInputCircuit,
+};
+use crate::{
+    prepare_verifying_key,
+    prover::{ProvingError, ProvingStage},
+    r1cs_to_qap::{LibsnarkReduction, R1CSToQAP},
+    Groth16,
+};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::GeneralEvaluationDomain;
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+        OptimizationGoal, SynthesisError, SynthesisMode, Variable,
+    },
+};
+use ark_std::UniformRand;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A circuit that sets `cancel` once it has been synthesized.
+struct CancellingCircuit<'a, F: Field> {
+    circuit: MySillyCircuit<F>,
+    cancel: &'a AtomicBool,
+}
+
+impl<'a, ConstraintF: Field> ConstraintSynthesizer<ConstraintF>
+    for CancellingCircuit<'a, ConstraintF>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        self.circuit.generate_constraints(cs)?;
+        self.cancel.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// A circuit that allocates an extra witness variable only when its witness
+/// is known, so its layout differs between setup and proving.
+struct ConditionalCircuit<F: Field> {
+    a: Option<F>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for ConditionalCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a.square())
+        })?;
+        cs.enforce_constraint(lc!() + a, lc!() + a, lc!() + c)?;
+
+        if let Some(a) = self.a {
+            let _ = cs.new_witness_variable(|| Ok(a))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A circuit proving `x^3 + x + 5 == y` for public `x` and `y`, in which `x`
+/// appears in the A and B terms of several constraints.
+struct CubicCircuit<F: Field> {
+    x: Option<F>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for CubicCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let x_value = || self.x.ok_or(SynthesisError::AssignmentMissing);
+        let x = cs.new_input_variable(x_value)?;
+        let y = cs.new_input_variable(|| {
+            let x = x_value()?;
+            Ok(x * x * x + x + ConstraintF::from(5u64))
+        })?;
+        let x2 = cs.new_witness_variable(|| Ok(x_value()?.square()))?;
+        let x3 = cs.new_witness_variable(|| {
+            let x = x_value()?;
+            Ok(x.square() * x)
+        })?;
+
+        cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x2)?;
+        cs.enforce_constraint(lc!() + x2, lc!() + x, lc!() + x3)?;
+        cs.enforce_constraint(lc!() + x + x2, lc!() + x, lc!() + x3 + x2)?;
+        cs.enforce_constraint(
+            lc!() + x3 + x + (ConstraintF::from(5u64), Variable::One),
+            lc!() + Variable::One,
+            lc!() + y,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Synthesize `circuit` in setup mode and assert that it has the expected
+/// number of instance variables (excluding the constant one) and witness
+/// variables.
+fn assert_circuit_layout<F, C>(circuit: C, expected_instance: usize, expected_witness: usize)
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F>,
+{
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+
+    assert_eq!(cs.num_instance_variables() - 1, expected_instance);
+    assert_eq!(cs.num_witness_variables(), expected_witness);
+}
+
+fn test_prove_and_verify_without_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(NoInputCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    // Only the constant-one instance variable remains.
+    assert_eq!(vk.gamma_abc_g1.len(), 1);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        NoInputCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[], &proof).unwrap());
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).is_err());
+}
+
+fn test_h_query_length_mismatch<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    // Same variables as `MySillyCircuit`, but enough constraints to need a larger
+    // evaluation domain, and hence a higher-degree H.
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || ManyConstraintsCircuit {
+        a: Some(a),
+        b: Some(b),
+        num_constraints: 20,
+    };
+    // 20 constraints and 2 instance variables need a domain of size 32.
+    assert_eq!(
+        Groth16::<E>::create_random_proof_with_reduction(circuit(), &pk, &mut rng).err(),
+        Some(ProvingError::QuotientLengthMismatch {
+            expected: pk.h_query.len() + 1,
+            actual: 32,
+        })
+    );
+    assert_eq!(
+        Groth16::<E>::prove(&pk, circuit(), &mut rng).err(),
+        Some(SynthesisError::PolynomialDegreeTooLarge)
+    );
+}
+
+fn test_prove_with_zero_randomness<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let zero = E::ScalarField::zero();
+    let nonzero = E::ScalarField::rand(&mut rng);
+    for (r, s) in [(zero, zero), (zero, nonzero), (nonzero, zero)] {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+
+        let proof = Groth16::<E>::create_proof_with_reduction(
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &pk,
+            r,
+            s,
+        )
+        .unwrap();
+
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap());
+    }
+}
+
+fn test_prove_without_b_g1_query<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let pk = Groth16::<E>::generate_random_parameters_without_b_g1_query(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk).unwrap();
+    assert!(pk.b_g1_query.is_empty());
+    assert!(!pk.b_g2_query.is_empty());
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let s = E::ScalarField::rand(&mut rng);
+    let proof =
+        Groth16::<E>::create_proof_with_reduction(circuit(), &pk, E::ScalarField::zero(), s)
+            .unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    let r = E::ScalarField::rand(&mut rng);
+    assert_eq!(
+        Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).err(),
+        Some(ProvingError::MissingBG1Query)
+    );
+}
+
+/// The constraint matrices of `circuit`, synthesized in setup mode.
+fn setup_matrices<F: PrimeField>(circuit: impl ConstraintSynthesizer<F>) -> ConstraintMatrices<F> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    cs.to_matrices().unwrap()
+}
+
+fn test_prove_with_witness_oracle<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    // Only the structure of the circuit is needed up front.
+    let matrices = setup_matrices(MySillyCircuit { a: None, b: None });
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+
+    let oracle =
+        Groth16::<E>::create_proof_with_witness_oracle(&pk, r, s, &matrices, |v| match v {
+            Variable::Instance(1) => a * b,
+            Variable::Witness(0) => a,
+            Variable::Witness(1) => b,
+            _ => unreachable!(),
+        })
+        .unwrap();
+    let eager = Groth16::<E>::create_proof_with_reduction(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &pk,
+        r,
+        s,
+    )
+    .unwrap();
+
+    assert_eq!(oracle, eager);
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &oracle).unwrap());
+
+    // Matrices of another circuit are rejected before any proving work.
+    let other = setup_matrices(NoInputCircuit { a: None, b: None });
+    assert_eq!(
+        Groth16::<E>::create_proof_with_witness_oracle(&pk, r, s, &other, |_| a).unwrap_err(),
+        ProvingError::InstanceLengthMismatch {
+            expected: 2,
+            actual: 1,
+        }
+    );
+}
+
+fn test_prove_with_reused_input<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(CubicCircuit { x: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let x = E::ScalarField::from(3u64);
+    let y = E::ScalarField::from(35u64);
+    let proof = Groth16::<E>::prove(&pk, CubicCircuit { x: Some(x) }, &mut rng).unwrap();
+
+    // The constraints are not all linear, so the quotient is not zero.
+    let cs = ConstraintSystem::new_ref();
+    CubicCircuit { x: Some(x) }
+        .generate_constraints(cs.clone())
+        .unwrap();
+    cs.finalize();
+    assert!(cs.is_satisfied().unwrap());
+    let h =
+        LibsnarkReduction::witness_map::<E::ScalarField, GeneralEvaluationDomain<E::ScalarField>>(
+            cs,
+        )
+        .unwrap();
+    assert!(h.iter().any(|h| !h.is_zero()));
+
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[x, y], &proof).unwrap());
+    assert!(
+        !Groth16::<E>::verify_with_processed_vk(&pvk, &[x + E::ScalarField::one(), y], &proof)
+            .unwrap()
+    );
+    assert!(
+        !Groth16::<E>::verify_with_processed_vk(&pvk, &[x, y + E::ScalarField::one()], &proof)
+            .unwrap()
+    );
+}
+
+fn test_prove_with_mismatched_matrices<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let matrices = setup_matrices(MySillyCircuit { a: None, b: None });
+    let other = setup_matrices(NoInputCircuit { a: None, b: None });
+
+    let (a, b) = (E::ScalarField::from(2u64), E::ScalarField::from(3u64));
+    let full_assignment = [E::ScalarField::one(), a * b, a, b];
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let prove = |matrices: &ConstraintMatrices<_>, num_inputs, full_assignment: &[_]| {
+        Groth16::<E>::create_proof_with_reduction_and_matrices(
+            &pk,
+            r,
+            s,
+            matrices,
+            num_inputs,
+            matrices.num_constraints,
+            full_assignment,
+        )
+    };
+
+    assert!(prove(&matrices, 2, &full_assignment).is_ok());
+    assert_eq!(
+        prove(&other, 2, &full_assignment).unwrap_err(),
+        ProvingError::InstanceLengthMismatch {
+            expected: 2,
+            actual: 1,
+        }
+    );
+    assert_eq!(
+        prove(&matrices, 2, &full_assignment[..1]).unwrap_err(),
+        ProvingError::InstanceLengthMismatch {
+            expected: 2,
+            actual: 1,
+        }
+    );
+    assert_eq!(
+        prove(&matrices, 2, &[full_assignment.as_slice(), &[a]].concat()).unwrap_err(),
+        ProvingError::WitnessLengthMismatch {
+            expected: 2,
+            actual: 3,
+        }
+    );
+}
+
+fn test_deterministic_setup_and_prove<E>()
+where
+    E: Pairing,
+{
+    let a = E::ScalarField::from(17u64);
+    let b = E::ScalarField::from(19u64);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let pk = generate_parameters_deterministic::<E, _>(MySillyCircuit { a: None, b: None });
+    let proof = create_proof_deterministic(circuit(), &pk);
+
+    assert_eq!(
+        pk,
+        generate_parameters_deterministic::<E, _>(MySillyCircuit { a: None, b: None })
+    );
+    assert_eq!(proof, create_proof_deterministic(circuit(), &pk));
+
+    let pvk = prepare_verifying_key::<E>(&pk.vk).unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap());
+}
+
+fn test_setup_and_prove<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let (pk, proof) = Groth16::<E>::setup_and_prove(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&pk.vk, &[a], &proof).unwrap());
+
+    // The key is also usable for further proofs.
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(b),
+            b: Some(a),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+}
+
+fn test_prove_with_weight_goal<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let pk = Groth16::<E>::generate_random_parameters_with_goal(
+        MySillyCircuit { a: None, b: None },
+        OptimizationGoal::Weight,
+        &mut rng,
+    )
+    .unwrap();
+
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::create_proof_with_reduction_and_goal(
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &pk,
+            E::ScalarField::rand(&mut rng),
+            E::ScalarField::rand(&mut rng),
+            OptimizationGoal::Weight,
+        )
+        .unwrap();
+
+        assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+        assert!(!Groth16::<E>::verify(&pk.vk, &[a], &proof).unwrap());
+    }
+}
+
+fn test_circuit_layouts<E>()
+where
+    E: Pairing,
+{
+    assert_circuit_layout::<E::ScalarField, _>(MySillyCircuit { a: None, b: None }, 1, 2);
+    assert_circuit_layout::<E::ScalarField, _>(NoInputCircuit { a: None, b: None }, 0, 3);
+}
+
+fn test_prove_with_changed_layout<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, _) = Groth16::<E>::setup(ConditionalCircuit { a: None }, &mut rng).unwrap();
+
+    let result = Groth16::<E>::create_random_proof_with_reduction(
+        ConditionalCircuit {
+            a: Some(E::ScalarField::rand(&mut rng)),
+        },
+        &pk,
+        &mut rng,
+    );
+    assert_eq!(
+        result.err(),
+        Some(ProvingError::WitnessLengthMismatch {
+            expected: 1,
+            actual: 2,
+        })
+    );
+}
+
+fn test_prove_from_assignments<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+
+    let h =
+        LibsnarkReduction::witness_map::<E::ScalarField, GeneralEvaluationDomain<_>>(cs.clone())
+            .unwrap();
+    let prover = cs.borrow().unwrap();
+    let input_assignment = &prover.instance_assignment[1..];
+    let aux_assignment = &prover.witness_assignment;
+
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let proof =
+        Groth16::<E>::prove_from_assignments(&pk, r, s, &h, input_assignment, aux_assignment)
+            .unwrap();
+    assert!(Groth16::<E>::verify(&vk, input_assignment, &proof).unwrap());
+
+    assert_eq!(
+        Groth16::<E>::prove_from_assignments(&pk, r, s, &h, &[], aux_assignment).err(),
+        Some(ProvingError::InstanceLengthMismatch {
+            expected: 2,
+            actual: 1,
+        })
+    );
+    assert_eq!(
+        Groth16::<E>::prove_from_assignments(&pk, r, s, &h, input_assignment, &aux_assignment[1..])
+            .err(),
+        Some(ProvingError::WitnessLengthMismatch {
+            expected: 2,
+            actual: 1,
+        })
+    );
+    assert_eq!(
+        Groth16::<E>::prove_from_assignments(&pk, r, s, &h[1..], input_assignment, aux_assignment)
+            .err(),
+        Some(ProvingError::QuotientLengthMismatch {
+            expected: h.len(),
+            actual: h.len() - 1,
+        })
+    );
+}
+
+fn test_create_proof_cancellable<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let cancel = AtomicBool::new(false);
+    let proof = Groth16::<E>::create_proof_cancellable(circuit(), &pk, r, s, &cancel).unwrap();
+    assert_eq!(
+        proof,
+        Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).unwrap()
+    );
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    // Cancelled during synthesis.
+    let cancelling = CancellingCircuit {
+        circuit: circuit(),
+        cancel: &cancel,
+    };
+    assert_eq!(
+        Groth16::<E>::create_proof_cancellable(cancelling, &pk, r, s, &cancel),
+        Err(ProvingError::Cancelled)
+    );
+
+    // Errors other than cancellation are passed through.
+    let cancel = AtomicBool::new(false);
+    assert_eq!(
+        Groth16::<E>::create_proof_cancellable(
+            MySillyCircuit::<E::ScalarField> { a: None, b: None },
+            &pk,
+            r,
+            s,
+            &cancel
+        ),
+        Err(ProvingError::Synthesis(SynthesisError::AssignmentMissing))
+    );
+}
+
+fn test_create_proof_with_progress<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let mut stages = Vec::new();
+    let proof = Groth16::<E>::create_proof_with_progress(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &pk,
+        &mut rng,
+        |stage| stages.push(stage),
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+    assert_eq!(
+        stages,
+        [
+            ProvingStage::Synthesized,
+            ProvingStage::WitnessMapped,
+            ProvingStage::CAccumulated,
+            ProvingStage::AComputed,
+            ProvingStage::BComputed,
+            ProvingStage::CComputed,
+        ]
+    );
+}
+
+mod bls12_377 {
+    use super::{
+        test_circuit_layouts, test_create_proof_cancellable, test_create_proof_with_progress,
+        test_deterministic_setup_and_prove, test_h_query_length_mismatch,
+        test_prove_and_verify_without_inputs, test_prove_from_assignments,
+        test_prove_with_changed_layout, test_prove_with_mismatched_matrices,
+        test_prove_with_reused_input, test_prove_with_weight_goal, test_prove_with_witness_oracle,
+        test_prove_with_zero_randomness, test_prove_without_b_g1_query, test_setup_and_prove,
+    };
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn prove_and_verify_without_inputs() {
+        test_prove_and_verify_without_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn h_query_length_mismatch() {
+        test_h_query_length_mismatch::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_zero_randomness() {
+        test_prove_with_zero_randomness::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_without_b_g1_query() {
+        test_prove_without_b_g1_query::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_witness_oracle() {
+        test_prove_with_witness_oracle::<Bls12_377>();
+    }
+
+    #[test]
+    fn deterministic_setup_and_prove() {
+        test_deterministic_setup_and_prove::<Bls12_377>();
+    }
+
+    #[test]
+    fn setup_and_prove() {
+        test_setup_and_prove::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_weight_goal() {
+        test_prove_with_weight_goal::<Bls12_377>();
+    }
+
+    #[test]
+    fn circuit_layouts() {
+        test_circuit_layouts::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_changed_layout() {
+        test_prove_with_changed_layout::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_from_assignments() {
+        test_prove_from_assignments::<Bls12_377>();
+    }
+
+    #[test]
+    fn create_proof_cancellable() {
+        test_create_proof_cancellable::<Bls12_377>();
+    }
+
+    #[test]
+    fn create_proof_with_progress() {
+        test_create_proof_with_progress::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_batch_seeded_known_answer() {
+        use super::{generate_parameters_deterministic, Groth16, MySillyCircuit};
+        use crate::Proof;
+        use ark_bls12_377::{Fq2, Fr, G1Affine, G2Affine};
+        use ark_ff::MontFp;
+
+        let pk =
+            generate_parameters_deterministic::<Bls12_377, _>(MySillyCircuit { a: None, b: None });
+        let circuits = || {
+            (0..2)
+                .map(|_| MySillyCircuit {
+                    a: Some(Fr::from(17u64)),
+                    b: Some(Fr::from(19u64)),
+                })
+                .collect::<Vec<_>>()
+        };
+        let proofs = Groth16::<Bls12_377>::prove_batch_seeded(&pk, circuits(), 42).unwrap();
+        assert_ne!(proofs[0], proofs[1]);
+
+        #[cfg(feature = "parallel")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .unwrap();
+            let single_threaded = pool
+                .install(|| Groth16::<Bls12_377>::prove_batch_seeded(&pk, circuits(), 42).unwrap());
+            assert_eq!(proofs, single_threaded);
+        }
+
+        // The second proof is randomized by the seed `42 || 1 || 0..`.
+        assert_eq!(
+            proofs[1],
+            Proof {
+                a: G1Affine::new(
+                    MontFp!("79284638464130371844543108055769768377510214526521764216472543606455539183861593780579875930380114467270498364240"),
+                    MontFp!("241662865755820292500900926871130393028463127169729303476732018930522298334071687226611579161557623667414887404461"),
+                ),
+                b: G2Affine::new(
+                    Fq2::new(
+                        MontFp!("194177083322514542827034313729895461605311124706111358750578918944021446027778640406319817717710309266347780863535"),
+                        MontFp!("209764590668308586399691422467577857669740292369549004655828753168652659059255988023333434614096588207622208664424"),
+                    ),
+                    Fq2::new(
+                        MontFp!("152721780057415407127136217545118554676919811953560735055070720951695411295721010999708391381353806809844589699154"),
+                        MontFp!("216357824377553426202663150009296154838521871223195522169481791036379175944760008965819244631522339015171630664321"),
+                    ),
+                ),
+                c: G1Affine::new(
+                    MontFp!("195922663825340170876106304261579831384518028232852288382285868360569134582622006499503914489834963616643425083326"),
+                    MontFp!("228155901292826890971745020534952603043945980908359283365012306378509197087310148909131160959523504020199361074691"),
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn deterministic_setup_and_prove_known_answer() {
+        use super::{
+            create_proof_deterministic, generate_parameters_deterministic, MySillyCircuit,
+        };
+        use ark_bls12_377::{Fq2, Fr, G1Affine, G2Affine};
+        use ark_ff::MontFp;
+
+        // The proof depends on every query of the key, so this pins both helpers.
+        let pk =
+            generate_parameters_deterministic::<Bls12_377, _>(MySillyCircuit { a: None, b: None });
+        let proof = create_proof_deterministic(
+            MySillyCircuit {
+                a: Some(Fr::from(17u64)),
+                b: Some(Fr::from(19u64)),
+            },
+            &pk,
+        );
+        assert_eq!(
+            proof.a,
+            G1Affine::new(
+                MontFp!("116498790548715866302253861137252206007724851683660761572400367787048574816883882761599902743671936558302932709396"),
+                MontFp!("235123886088506562741732331162982672740828287925576980480693253196118536470962277825123014752315284662926538088442"),
+            )
+        );
+        assert_eq!(
+            proof.b,
+            G2Affine::new(
+                Fq2::new(
+                    MontFp!("236077924491731107519237212210227234439258887946089884371373320622888729727237393922354140895625322368327544720870"),
+                    MontFp!("46120251629645712233560079437343622550151949055622618036192571426003440859812636038942638267070697176237198231662"),
+                ),
+                Fq2::new(
+                    MontFp!("176379023068789171142387899205652852306392145969338512858002194556432517079226331019973943264044573428987250839570"),
+                    MontFp!("42838153904431883443911936573488494706749238943079941596549629863065019825767723057805769746628012129171633466827"),
+                ),
+            )
+        );
+        assert_eq!(
+            proof.c,
+            G1Affine::new(
+                MontFp!("83677542381831247828604340245241797331947247568578358126841454591513136887336802210308204105819855818655156984716"),
+                MontFp!("213938009987272017383572610372180898984125577038286737480326191690465081616299222540546198796521613382200371422968"),
+            )
+        );
+    }
+
+    #[test]
+    fn prove_with_mismatched_matrices() {
+        test_prove_with_mismatched_matrices::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_reused_input() {
+        test_prove_with_reused_input::<Bls12_377>();
+    }
+}
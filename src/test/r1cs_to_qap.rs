@@ -0,0 +1,166 @@
+use super::{seeded_rng, MySillyCircuit};
+use crate::{
+    generator::SetupError,
+    prover::ProvingError,
+    r1cs_to_qap::{
+        check_domain_size, max_domain_size, DomainSizeError, LibsnarkReduction, R1CSToQAP,
+    },
+    Groth16,
+};
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+    OptimizationGoal, SynthesisError,
+};
+use ark_std::UniformRand;
+
+/// A reduction that delegates to [`LibsnarkReduction`], standing in for an
+/// independent implementation that is expected to produce the same `h`. It
+/// supports evaluation domains of size at most `MAX_DOMAIN_SIZE`.
+struct DelegatingReduction<const MAX_DOMAIN_SIZE: usize = { usize::MAX }>;
+
+impl<const MAX_DOMAIN_SIZE: usize> R1CSToQAP for DelegatingReduction<MAX_DOMAIN_SIZE> {
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError> {
+        LibsnarkReduction::instance_map_with_evaluation::<F, D>(cs, t)
+    }
+
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> Result<Vec<F>, SynthesisError> {
+        LibsnarkReduction::witness_map_from_matrices::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+        )
+    }
+
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        zt: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        LibsnarkReduction::h_query_scalars::<F, D>(max_power, t, zt, delta_inverse)
+    }
+
+    fn check_domain_size<F: PrimeField>(
+        num_constraints: usize,
+        num_instance_variables: usize,
+    ) -> Result<usize, DomainSizeError> {
+        let required = check_domain_size::<F>(num_constraints, num_instance_variables)?;
+        if required > MAX_DOMAIN_SIZE {
+            return Err(DomainSizeError {
+                required,
+                max: MAX_DOMAIN_SIZE,
+            });
+        }
+        Ok(required)
+    }
+}
+
+fn test_commit_h_poly<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+
+    let libsnark = Groth16::<E>::commit_h_poly(&pk, cs.clone()).unwrap();
+    let delegating = Groth16::<E, DelegatingReduction>::commit_h_poly(&pk, cs.clone()).unwrap();
+    assert_eq!(libsnark, delegating);
+}
+
+fn test_domain_size_limit<E>()
+where
+    E: Pairing,
+{
+    let max = max_domain_size::<E::ScalarField>();
+    assert_eq!(check_domain_size::<E::ScalarField>(max - 1, 1), Ok(max));
+
+    // A mocked constraint count one past the limit.
+    let err = check_domain_size::<E::ScalarField>(max, 1).unwrap_err();
+    assert_eq!(
+        err,
+        DomainSizeError {
+            required: max + 1,
+            max
+        }
+    );
+    assert_eq!(
+        SynthesisError::from(err),
+        SynthesisError::PolynomialDegreeTooLarge
+    );
+
+    // `MySillyCircuit` has 6 constraints and 2 instance variables.
+    let mut rng = seeded_rng();
+    let err = DomainSizeError {
+        required: 8,
+        max: 4,
+    };
+    assert_eq!(
+        Groth16::<E, DelegatingReduction<4>>::generate_random_parameters_with_reduction(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+        )
+        .err(),
+        Some(SetupError::DomainSize(err))
+    );
+
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+    )
+    .unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    assert_eq!(
+        Groth16::<E, DelegatingReduction<4>>::create_random_proof_with_reduction(
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &pk,
+            &mut rng,
+        )
+        .err(),
+        Some(ProvingError::DomainSize(err))
+    );
+}
+
+mod bls12_377 {
+    use super::{test_commit_h_poly, test_domain_size_limit};
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn commit_h_poly() {
+        test_commit_h_poly::<Bls12_377>();
+    }
+
+    #[test]
+    fn domain_size_limit() {
+        test_domain_size_limit::<Bls12_377>();
+    }
+}
@@ -0,0 +1,580 @@
+use super::{
+    prove_silly, seeded_rng, ManyInputsCircuit, MySillyCircuit, NoInputCircuit, SillyProof,
+};
+use crate::{
+    prepare_verifying_key, verifier::READER_CHUNK_SIZE, Groth16, InputError, VerifyingKey,
+};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+
+fn test_precheck_rejects_identity<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let prepared_inputs = Groth16::<E>::prepare_inputs(&pvk, &[a * b]).unwrap();
+
+    assert!(Groth16::<E>::check_proof_structure(&proof));
+    assert!(
+        Groth16::<E>::verify_proof_with_prepared_inputs_and_precheck(
+            &pvk,
+            &proof,
+            &prepared_inputs,
+            true
+        )
+        .unwrap()
+    );
+
+    let mut bad_proof = proof.clone();
+    bad_proof.a = E::G1Affine::zero();
+    assert!(!Groth16::<E>::check_proof_structure(&bad_proof));
+    assert!(
+        !Groth16::<E>::verify_proof_with_prepared_inputs_and_precheck(
+            &pvk,
+            &bad_proof,
+            &prepared_inputs,
+            true
+        )
+        .unwrap()
+    );
+}
+
+fn test_verify_proof_from_limbs<E, const N: usize>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+    let c = a * b;
+
+    let mut limbs = [0u64; N];
+    limbs.copy_from_slice(c.into_bigint().as_ref());
+    assert!(Groth16::<E>::verify_proof_from_limbs(&pvk, &proof, &[limbs]).unwrap());
+
+    limbs.copy_from_slice(a.into_bigint().as_ref());
+    assert!(!Groth16::<E>::verify_proof_from_limbs(&pvk, &proof, &[limbs]).unwrap());
+
+    // Limbs that are not below the modulus are not canonical.
+    assert_eq!(
+        Groth16::<E>::verify_proof_from_limbs(&pvk, &proof, &[[u64::MAX; N]]).err(),
+        Some(InputError::NonCanonical(0))
+    );
+    limbs.copy_from_slice(E::ScalarField::MODULUS.as_ref());
+    assert_eq!(
+        Groth16::<E>::verify_proof_from_limbs(&pvk, &proof, &[limbs]).err(),
+        Some(InputError::NonCanonical(0))
+    );
+}
+
+fn test_verification_cost<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let cost = pvk.verification_cost();
+    // `verify_proof_with_prepared_inputs` runs a single multi-Miller loop over
+    // `(a, b)`, `(prepared_inputs, -gamma)` and `(c, -delta)`.
+    assert_eq!(cost.num_pairings, 3);
+    assert_eq!(cost.num_final_exponentiations, 1);
+    assert_eq!(cost.input_msm_size, 1);
+    assert_eq!(cost.estimated_bn254_gas, 45_000 + 4 * 34_000 + 6_150);
+}
+
+fn test_prepare_default_vk_rejected<E>()
+where
+    E: Pairing,
+{
+    assert_eq!(
+        prepare_verifying_key::<E>(&VerifyingKey::default()).err(),
+        Some(SynthesisError::MalformedVerifyingKey)
+    );
+    assert!(Groth16::<E>::process_vk(&VerifyingKey::default()).is_err());
+}
+
+fn test_verify_miller_loop_only<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    for inputs in [[a * b], [a]] {
+        let miller_loop_output =
+            Groth16::<E>::verify_miller_loop_only(&pvk, &proof, &inputs).unwrap();
+        assert_eq!(
+            Groth16::<E>::check_final_exp(&pvk, miller_loop_output).unwrap(),
+            Groth16::<E>::verify_proof(&pvk, &proof, &inputs).unwrap()
+        );
+    }
+    assert!(Groth16::<E>::verify_miller_loop_only(&pvk, &proof, &[]).is_err());
+}
+
+fn test_verify_proof_linear_combination<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        mut rng,
+        vk,
+        a,
+        b,
+        proof,
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let one = E::ScalarField::one();
+    for inputs in [vec![a * b], vec![a]] {
+        assert_eq!(
+            Groth16::<E>::verify_proof_linear_combination(&pvk, &proof, &[inputs.clone()], &[one])
+                .unwrap(),
+            Groth16::<E>::verify_proof(&pvk, &proof, &inputs).unwrap()
+        );
+    }
+
+    // c * x + (1 - c) * x == x.
+    let c = E::ScalarField::rand(&mut rng);
+    assert!(Groth16::<E>::verify_proof_linear_combination(
+        &pvk,
+        &proof,
+        &[vec![a * b], vec![a * b]],
+        &[c, one - c]
+    )
+    .unwrap());
+    assert_eq!(
+        Groth16::<E>::verify_proof_linear_combination(&pvk, &proof, &[vec![a * b]], &[]),
+        Err(InputError::LengthMismatch {
+            expected: 1,
+            actual: 0,
+        })
+    );
+    assert_eq!(
+        Groth16::<E>::verify_proof_linear_combination(&pvk, &proof, &[vec![a * b]], &[one, one]),
+        Err(InputError::LengthMismatch {
+            expected: 1,
+            actual: 2,
+        })
+    );
+    assert_eq!(
+        Groth16::<E>::verify_proof_linear_combination(&pvk, &proof, &[vec![]], &[one]),
+        Err(InputError::Synthesis(SynthesisError::MalformedVerifyingKey))
+    );
+}
+
+fn test_batch_verify_proofs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let mut proofs = Vec::new();
+    let mut public_inputs = Vec::new();
+    for _ in 0..16 {
+        let (proof, a, b) = prove_silly(&pk, &mut rng);
+        proofs.push(proof);
+        public_inputs.push(vec![a * b]);
+    }
+
+    assert!(Groth16::<E>::batch_verify_proofs(&pvk, &proofs, &public_inputs, &mut rng).unwrap());
+    assert!(Groth16::<E>::batch_verify_proofs(&pvk, &[], &[], &mut rng).unwrap());
+
+    let mut wrong_inputs = public_inputs.clone();
+    wrong_inputs[7][0] += E::ScalarField::one();
+    assert!(!Groth16::<E>::batch_verify_proofs(&pvk, &proofs, &wrong_inputs, &mut rng).unwrap());
+
+    let mut swapped = proofs.clone();
+    swapped.swap(3, 11);
+    assert!(!Groth16::<E>::batch_verify_proofs(&pvk, &swapped, &public_inputs, &mut rng).unwrap());
+
+    assert!(
+        Groth16::<E>::batch_verify_proofs(&pvk, &proofs, &public_inputs[1..], &mut rng).is_err()
+    );
+}
+
+fn test_batch_verify_proofs_mixed_validity<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let mut proofs = Vec::new();
+    let mut public_inputs = Vec::new();
+    for _ in 0..37 {
+        let (proof, a, b) = prove_silly(&pk, &mut rng);
+        proofs.push(proof);
+        public_inputs.push(vec![a * b]);
+    }
+    assert!(Groth16::<E>::batch_verify_proofs(&pvk, &proofs, &public_inputs, &mut rng).unwrap());
+
+    // A single invalid proof anywhere in the batch makes it fail.
+    for i in [0, 18, 36] {
+        let mut mixed = proofs.clone();
+        mixed[i].a = (mixed[i].a + E::G1Affine::generator()).into_affine();
+        assert!(
+            !Groth16::<E>::batch_verify_proofs(&pvk, &mixed, &public_inputs, &mut rng).unwrap()
+        );
+    }
+}
+
+fn test_batch_verify_mixed<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (silly_pk, silly_vk) =
+        Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let silly_pvk = prepare_verifying_key::<E>(&silly_vk).unwrap();
+    let (no_input_pk, no_input_vk) =
+        Groth16::<E>::setup(NoInputCircuit { a: None, b: None }, &mut rng).unwrap();
+    let no_input_pvk = prepare_verifying_key::<E>(&no_input_vk).unwrap();
+
+    let mut batch = Vec::new();
+    for _ in 0..3 {
+        let (silly_proof, a, b) = prove_silly(&silly_pk, &mut rng);
+        batch.push((silly_pvk.clone(), silly_proof, vec![a * b]));
+
+        let no_input_proof = Groth16::<E>::prove(
+            &no_input_pk,
+            NoInputCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        batch.push((no_input_pvk.clone(), no_input_proof, vec![]));
+    }
+
+    assert!(Groth16::<E>::batch_verify_mixed(&batch, &mut rng).unwrap());
+    assert!(Groth16::<E>::batch_verify_mixed(&[], &mut rng).unwrap());
+
+    // A proof checked against the other circuit's key fails the whole batch.
+    let mut wrong_key = batch.clone();
+    wrong_key[1].0 = silly_pvk.clone();
+    wrong_key[1].2 = vec![E::ScalarField::one()];
+    assert!(!Groth16::<E>::batch_verify_mixed(&wrong_key, &mut rng).unwrap());
+
+    let mut wrong_inputs = batch.clone();
+    wrong_inputs[4].2[0] += E::ScalarField::one();
+    assert!(!Groth16::<E>::batch_verify_mixed(&wrong_inputs, &mut rng).unwrap());
+}
+
+fn test_verify_with_prepared_affine_inputs<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    for inputs in [[a * b], [a]] {
+        let prepared_inputs = Groth16::<E>::prepare_inputs(&pvk, &inputs).unwrap();
+        assert_eq!(
+            Groth16::<E>::verify_proof_with_prepared_affine_inputs(
+                &pvk,
+                &proof,
+                &prepared_inputs.into_affine()
+            )
+            .unwrap(),
+            Groth16::<E>::verify_proof_with_prepared_inputs(&pvk, &proof, &prepared_inputs)
+                .unwrap()
+        );
+    }
+}
+
+fn test_verify_proof_sparse_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let num_inputs = 32;
+    let (pk, vk) = Groth16::<E>::setup(
+        ManyInputsCircuit::<E::ScalarField> {
+            inputs: vec![None; num_inputs],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let mut inputs = vec![E::ScalarField::zero(); num_inputs];
+    inputs[3] = E::ScalarField::rand(&mut rng);
+    inputs[17] = E::ScalarField::rand(&mut rng);
+    inputs[31] = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        ManyInputsCircuit {
+            inputs: inputs.iter().copied().map(Some).collect(),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify_proof(&pvk, &proof, &inputs).unwrap());
+
+    let sparse_inputs = [
+        (31, inputs[31]),
+        (3, inputs[3]),
+        (17, inputs[17]),
+        (5, E::ScalarField::zero()),
+    ];
+    assert!(Groth16::<E>::verify_proof_sparse_inputs(&pvk, &proof, &sparse_inputs).unwrap());
+    assert!(!Groth16::<E>::verify_proof_sparse_inputs(&pvk, &proof, &sparse_inputs[1..]).unwrap());
+    assert_eq!(
+        Groth16::<E>::verify_proof_sparse_inputs(
+            &pvk,
+            &proof,
+            &[(num_inputs, E::ScalarField::one())]
+        )
+        .err(),
+        Some(InputError::IndexOutOfRange {
+            index: num_inputs,
+            num_inputs,
+        })
+    );
+}
+
+fn test_verify_from_reader<E>()
+where
+    E: Pairing,
+{
+    let SillyProof {
+        vk, a, b, proof, ..
+    } = SillyProof::<E>::new();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let mut bytes = Vec::new();
+    vk.serialize_compressed(&mut bytes).unwrap();
+
+    for inputs in [vec![a * b], vec![a]] {
+        assert_eq!(
+            Groth16::<E>::verify_from_reader(&bytes[..], &proof, &inputs).unwrap(),
+            Groth16::<E>::verify_proof(&pvk, &proof, &inputs).unwrap()
+        );
+    }
+    assert!(Groth16::<E>::verify_from_reader(&bytes[..], &proof, &[a * b]).unwrap());
+    assert!(Groth16::<E>::verify_from_reader(&bytes[..], &proof, &[]).is_err());
+    assert!(Groth16::<E>::verify_from_reader(&bytes[..bytes.len() - 1], &proof, &[a * b]).is_err());
+
+    // Enough inputs to span more than one chunk of the key.
+    let mut rng = seeded_rng();
+    let inputs = (0..READER_CHUNK_SIZE + 1)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = |inputs: &[E::ScalarField]| ManyInputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit(&inputs), &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit(&inputs), &mut rng).unwrap();
+
+    let mut bytes = Vec::new();
+    vk.serialize_compressed(&mut bytes).unwrap();
+
+    let mut tampered = inputs.clone();
+    tampered[READER_CHUNK_SIZE] += E::ScalarField::one();
+    for inputs in [&inputs, &tampered] {
+        assert_eq!(
+            Groth16::<E>::verify_from_reader(&bytes[..], &proof, &inputs).unwrap(),
+            Groth16::<E>::verify_proof(&pvk, &proof, &inputs).unwrap()
+        );
+    }
+    assert!(Groth16::<E>::verify_from_reader(&bytes[..], &proof, &inputs).unwrap());
+}
+
+fn test_proves_same_statement<E>()
+where
+    E: Pairing,
+{
+    let mut rng = seeded_rng();
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = E::ScalarField::rand(&mut rng);
+    let mut prove = |a, b| {
+        Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap()
+    };
+    let proof1 = prove(a, b);
+    let proof2 = prove(a, b);
+    // Same statement, different witness.
+    let proof3 = prove(a * b * c.inverse().unwrap(), c);
+    let other = prove(a, c);
+    assert_ne!(proof1, proof2);
+
+    let inputs = [a * b];
+    assert!(Groth16::<E>::proves_same_statement(&pvk, &proof1, &proof2, &inputs).unwrap());
+    assert!(Groth16::<E>::proves_same_statement(&pvk, &proof1, &proof3, &inputs).unwrap());
+    assert!(!Groth16::<E>::proves_same_statement(&pvk, &proof1, &other, &inputs).unwrap());
+    assert!(!Groth16::<E>::proves_same_statement(&pvk, &other, &proof2, &inputs).unwrap());
+}
+
+mod bls12_377 {
+    use super::{
+        test_batch_verify_mixed, test_batch_verify_proofs, test_batch_verify_proofs_mixed_validity,
+        test_precheck_rejects_identity, test_prepare_default_vk_rejected,
+        test_proves_same_statement, test_verification_cost, test_verify_from_reader,
+        test_verify_miller_loop_only, test_verify_proof_from_limbs,
+        test_verify_proof_linear_combination, test_verify_proof_sparse_inputs,
+        test_verify_with_prepared_affine_inputs,
+    };
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn precheck_rejects_identity() {
+        test_precheck_rejects_identity::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proof_from_limbs() {
+        test_verify_proof_from_limbs::<Bls12_377, 4>();
+    }
+
+    #[test]
+    fn verification_cost() {
+        test_verification_cost::<Bls12_377>();
+    }
+
+    #[test]
+    fn prepare_default_vk_rejected() {
+        test_prepare_default_vk_rejected::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_miller_loop_only() {
+        test_verify_miller_loop_only::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proof_linear_combination() {
+        test_verify_proof_linear_combination::<Bls12_377>();
+    }
+
+    #[test]
+    fn batch_verify_proofs() {
+        test_batch_verify_proofs::<Bls12_377>();
+    }
+
+    #[test]
+    fn batch_verify_mixed() {
+        test_batch_verify_mixed::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_with_prepared_affine_inputs() {
+        test_verify_with_prepared_affine_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn batch_verify_proofs_mixed_validity() {
+        test_batch_verify_proofs_mixed_validity::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proof_sparse_inputs() {
+        test_verify_proof_sparse_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_from_reader() {
+        test_verify_from_reader::<Bls12_377>();
+    }
+
+    #[test]
+    fn proves_same_statement() {
+        test_proves_same_statement::<Bls12_377>();
+    }
+}
+
+mod bw6_761 {
+    use super::{
+        test_batch_verify_mixed, test_batch_verify_proofs, test_batch_verify_proofs_mixed_validity,
+        test_precheck_rejects_identity, test_proves_same_statement, test_verify_miller_loop_only,
+        test_verify_proof_from_limbs, test_verify_proof_linear_combination,
+        test_verify_proof_sparse_inputs, test_verify_with_prepared_affine_inputs,
+    };
+    use ark_bw6_761::BW6_761;
+
+    #[test]
+    fn precheck_rejects_identity() {
+        test_precheck_rejects_identity::<BW6_761>();
+    }
+
+    #[test]
+    fn verify_proof_from_limbs() {
+        test_verify_proof_from_limbs::<BW6_761, 6>();
+    }
+
+    #[test]
+    fn verify_miller_loop_only() {
+        test_verify_miller_loop_only::<BW6_761>();
+    }
+
+    #[test]
+    fn verify_proof_linear_combination() {
+        test_verify_proof_linear_combination::<BW6_761>();
+    }
+
+    #[test]
+    fn batch_verify_proofs() {
+        test_batch_verify_proofs::<BW6_761>();
+    }
+
+    #[test]
+    fn batch_verify_proofs_mixed_validity() {
+        test_batch_verify_proofs_mixed_validity::<BW6_761>();
+    }
+
+    #[test]
+    fn batch_verify_mixed() {
+        test_batch_verify_mixed::<BW6_761>();
+    }
+
+    #[test]
+    fn verify_with_prepared_affine_inputs() {
+        test_verify_with_prepared_affine_inputs::<BW6_761>();
+    }
+
+    #[test]
+    fn verify_proof_sparse_inputs() {
+        test_verify_proof_sparse_inputs::<BW6_761>();
+    }
+
+    #[test]
+    fn proves_same_statement() {
+        test_proves_same_statement::<BW6_761>();
+    }
+}
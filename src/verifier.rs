@@ -1,22 +1,63 @@
-use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_ff::PrimeField;
+use ark_ec::{
+    pairing::{MillerLoopOutput, Pairing},
+    AffineRepr, CurveGroup, VariableBaseMSM,
+};
+use ark_ff::{BigInteger, Field, One, PrimeField, UniformRand, Zero};
+use ark_std::{cfg_chunks, rand::Rng, vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use crate::{r1cs_to_qap::R1CSToQAP, Groth16};
 
-use super::{PreparedVerifyingKey, Proof, VerifyingKey};
+use super::{CompactVerifyingKey, InputError, PreparedVerifyingKey, Proof, VerifyingKey};
 
 use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, Valid};
 
 use core::ops::{AddAssign, Neg};
 
-/// Prepare the verifying key `vk` for use in proof verification.
-pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
-    PreparedVerifyingKey {
+/// The number of elements of `gamma_abc_g1` that [`Groth16::verify_from_reader`]
+/// buffers for each multi-scalar multiplication.
+pub(crate) const READER_CHUNK_SIZE: usize = 1024;
+
+/// Prepare the verifying key `vk` for use in proof verification. Returns
+/// [`SynthesisError::MalformedVerifyingKey`] if any of the generators in `vk` is
+/// the identity, as is the case for a default or corrupted key.
+pub fn prepare_verifying_key<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> R1CSResult<PreparedVerifyingKey<E>> {
+    if vk.alpha_g1.is_zero()
+        || vk.beta_g2.is_zero()
+        || vk.gamma_g2.is_zero()
+        || vk.delta_g2.is_zero()
+    {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    Ok(PreparedVerifyingKey {
         vk: vk.clone(),
         alpha_g1_beta_g2: E::pairing(vk.alpha_g1, vk.beta_g2).0,
         gamma_g2_neg_pc: vk.gamma_g2.into_group().neg().into_affine().into(),
         delta_g2_neg_pc: vk.delta_g2.into_group().neg().into_affine().into(),
+    })
+}
+
+/// Reconstruct a field element from its little-endian `u64` limbs, returning
+/// `None` if the limbs do not encode a canonical element of `F`.
+fn scalar_from_limbs<F: PrimeField, const N: usize>(limbs: &[u64; N]) -> Option<F> {
+    let size = F::zero().uncompressed_size();
+    let mut bytes = limbs
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes())
+        .collect::<Vec<_>>();
+    if bytes.len() < size {
+        bytes.resize(size, 0);
     }
+    if bytes[size..].iter().any(|b| *b != 0) {
+        return None;
+    }
+    F::deserialize_uncompressed(&bytes[..size]).ok()
 }
 
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
@@ -38,6 +79,13 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         Ok(g_ic)
     }
 
+    /// Cheaply check that `proof` is structurally well-formed: none of its elements is the
+    /// identity, and `proof.b` lies in the prime-order subgroup of `E::G2`. Passing this check
+    /// does not imply that the proof is valid.
+    pub fn check_proof_structure(proof: &Proof<E>) -> bool {
+        !proof.a.is_zero() && !proof.b.is_zero() && !proof.c.is_zero() && proof.b.check().is_ok()
+    }
+
     /// Verify a Groth16 proof `proof` against the prepared verification key `pvk` and prepared public
     /// inputs. This should be preferred over [`verify_proof`] if the instance's public inputs are
     /// known in advance.
@@ -46,20 +94,87 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         proof: &Proof<E>,
         prepared_inputs: &E::G1,
     ) -> R1CSResult<bool> {
-        let qap = E::multi_miller_loop(
+        Self::verify_proof_with_prepared_inputs_and_precheck(pvk, proof, prepared_inputs, false)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk` and prepared public
+    /// inputs. If `precheck` is set, proofs failing [`Self::check_proof_structure`] are rejected
+    /// before any pairing is computed, which limits the work an adversarial proof can force.
+    pub fn verify_proof_with_prepared_inputs_and_precheck(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: &E::G1,
+        precheck: bool,
+    ) -> R1CSResult<bool> {
+        if precheck && !Self::check_proof_structure(proof) {
+            return Ok(false);
+        }
+
+        Self::verify_proof_with_prepared_affine_inputs(pvk, proof, &prepared_inputs.into_affine())
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk` and prepared public
+    /// inputs in affine form. When verifying many proofs of the same instance, converting the
+    /// output of [`Self::prepare_inputs`] to affine once avoids a field inversion per proof.
+    pub fn verify_proof_with_prepared_affine_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: &E::G1Affine,
+    ) -> R1CSResult<bool> {
+        let qap = Self::miller_loop_with_prepared_inputs(
+            &pvk.gamma_g2_neg_pc,
+            &pvk.delta_g2_neg_pc,
+            proof,
+            *prepared_inputs,
+        );
+        Self::check_final_exp(pvk, qap)
+    }
+
+    fn miller_loop_with_prepared_inputs(
+        gamma_g2_neg_pc: &E::G2Prepared,
+        delta_g2_neg_pc: &E::G2Prepared,
+        proof: &Proof<E>,
+        prepared_inputs: E::G1Affine,
+    ) -> MillerLoopOutput<E> {
+        E::multi_miller_loop(
             [
                 <E::G1Affine as Into<E::G1Prepared>>::into(proof.a),
-                prepared_inputs.into_affine().into(),
+                prepared_inputs.into(),
                 proof.c.into(),
             ],
             [
                 proof.b.into(),
-                pvk.gamma_g2_neg_pc.clone(),
-                pvk.delta_g2_neg_pc.clone(),
+                gamma_g2_neg_pc.clone(),
+                delta_g2_neg_pc.clone(),
             ],
-        );
+        )
+    }
 
-        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+    /// Compute the verification equation for `proof` up to, but excluding, the final
+    /// exponentiation. This lets a recursive verifier defer or batch the final exponentiation;
+    /// pass the result to [`Self::check_final_exp`] to obtain the decision of [`verify_proof`].
+    pub fn verify_miller_loop_only(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<MillerLoopOutput<E>> {
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
+        Ok(Self::miller_loop_with_prepared_inputs(
+            &pvk.gamma_g2_neg_pc,
+            &pvk.delta_g2_neg_pc,
+            proof,
+            prepared_inputs.into_affine(),
+        ))
+    }
+
+    /// Apply the final exponentiation to a Miller loop output produced by
+    /// [`Self::verify_miller_loop_only`] and check the result against `pvk`.
+    pub fn check_final_exp(
+        pvk: &PreparedVerifyingKey<E>,
+        miller_loop_output: MillerLoopOutput<E>,
+    ) -> R1CSResult<bool> {
+        let test = E::final_exponentiation(miller_loop_output)
+            .ok_or(SynthesisError::UnexpectedIdentity)?;
 
         Ok(test.0 == pvk.alpha_g1_beta_g2)
     }
@@ -74,4 +189,367 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
         Self::verify_proof_with_prepared_inputs(pvk, proof, &prepared_inputs)
     }
+
+    /// Check that `proof1` and `proof2` are both valid proofs for the instance
+    /// `public_inputs` against the prepared verification key `pvk`, e.g. to
+    /// deduplicate proofs that differ only in their randomization. The inputs are
+    /// prepared once. Proofs are witness-indistinguishable, so this cannot tell
+    /// whether both proofs were created from the same witness.
+    pub fn proves_same_statement(
+        pvk: &PreparedVerifyingKey<E>,
+        proof1: &Proof<E>,
+        proof2: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?.into_affine();
+        Ok(
+            Self::verify_proof_with_prepared_affine_inputs(pvk, proof1, &prepared_inputs)?
+                && Self::verify_proof_with_prepared_affine_inputs(pvk, proof2, &prepared_inputs)?,
+        )
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
+    /// with respect to public inputs given as `(index, value)` pairs. Inputs that are
+    /// not listed are zero, and values for the same index are added. Indices refer to
+    /// positions in the public input vector, excluding the constant one. Only the
+    /// nonzero inputs contribute to the MSM over `gamma_abc_g1`.
+    pub fn verify_proof_sparse_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        sparse_inputs: &[(usize, E::ScalarField)],
+    ) -> Result<bool, InputError> {
+        let (constant, inputs) = pvk
+            .vk
+            .gamma_abc_g1
+            .split_first()
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        let mut bases = Vec::with_capacity(sparse_inputs.len());
+        let mut scalars = Vec::with_capacity(sparse_inputs.len());
+        for (i, value) in sparse_inputs {
+            let base = inputs.get(*i).ok_or(InputError::IndexOutOfRange {
+                index: *i,
+                num_inputs: inputs.len(),
+            })?;
+            if !value.is_zero() {
+                bases.push(*base);
+                scalars.push(value.into_bigint());
+            }
+        }
+
+        let prepared_inputs = *constant + E::G1::msm_bigint(&bases, &scalars);
+        Ok(Self::verify_proof_with_prepared_inputs(
+            pvk,
+            proof,
+            &prepared_inputs,
+        )?)
+    }
+
+    /// Verify a Groth16 proof `proof` against a verification key read from `vk_reader`
+    /// in its compressed canonical encoding, with respect to the instance
+    /// `public_inputs`. The elements of `gamma_abc_g1` are read and folded into the
+    /// prepared inputs 1024 at a time, so the key is never held in memory in full.
+    pub fn verify_from_reader<R: Read>(
+        mut vk_reader: R,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        fn read<T: CanonicalDeserialize, R: Read>(reader: &mut R) -> R1CSResult<T> {
+            T::deserialize_compressed(reader).map_err(|_| SynthesisError::MalformedVerifyingKey)
+        }
+
+        let alpha_g1 = read(&mut vk_reader)?;
+        let beta_g2 = read(&mut vk_reader)?;
+        let gamma_g2 = read(&mut vk_reader)?;
+        let delta_g2 = read(&mut vk_reader)?;
+        let gamma_abc_g1_len: u64 = read(&mut vk_reader)?;
+        if (public_inputs.len() + 1) as u64 != gamma_abc_g1_len {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let mut prepared_inputs = read::<E::G1Affine, _>(&mut vk_reader)?.into_group();
+        for inputs in public_inputs.chunks(READER_CHUNK_SIZE) {
+            let bases = inputs
+                .iter()
+                .map(|_| read(&mut vk_reader))
+                .collect::<R1CSResult<Vec<E::G1Affine>>>()?;
+            let scalars = inputs.iter().map(|i| i.into_bigint()).collect::<Vec<_>>();
+            prepared_inputs.add_assign(E::G1::msm_bigint(&bases, &scalars));
+        }
+
+        let vk = VerifyingKey {
+            alpha_g1,
+            beta_g2,
+            gamma_g2,
+            delta_g2,
+            gamma_abc_g1: Vec::new(),
+        };
+        let pvk = prepare_verifying_key(&vk)?;
+        Self::verify_proof_with_prepared_affine_inputs(&pvk, proof, &prepared_inputs.into_affine())
+    }
+
+    /// Verify a Groth16 proof `proof` against the compact verification key `cvk`,
+    /// with respect to the instance `public_inputs`. The elements of
+    /// `cvk.gamma_abc_g1` are decompressed as the inputs are prepared.
+    pub fn verify_proof_compact(
+        cvk: &CompactVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        if (public_inputs.len() + 1) != cvk.gamma_abc_g1_len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let mut gamma_abc_g1 = cvk.gamma_abc_g1();
+        let mut prepared_inputs = gamma_abc_g1
+            .next()
+            .ok_or(SynthesisError::MalformedVerifyingKey)?
+            .into_group();
+        for (i, b) in public_inputs.iter().zip(gamma_abc_g1) {
+            prepared_inputs.add_assign(&b.mul_bigint(i.into_bigint()));
+        }
+
+        let qap = Self::miller_loop_with_prepared_inputs(
+            &cvk.gamma_g2_neg_pc,
+            &cvk.delta_g2_neg_pc,
+            proof,
+            prepared_inputs.into_affine(),
+        );
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        Ok(test.0 == cvk.alpha_g1_beta_g2)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
+    /// with respect to the linear combination `sum_j coeffs[j] * input_sets[j]` of
+    /// several instances, as used by folding and aggregation schemes. The prepared
+    /// inputs of each set, including its `gamma_abc_g1[0]` term, are scaled by the
+    /// corresponding coefficient before a single verification. A different number
+    /// of coefficients and input sets is reported as [`InputError::LengthMismatch`].
+    pub fn verify_proof_linear_combination(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        input_sets: &[Vec<E::ScalarField>],
+        coeffs: &[E::ScalarField],
+    ) -> Result<bool, InputError> {
+        if input_sets.len() != coeffs.len() {
+            return Err(InputError::LengthMismatch {
+                expected: input_sets.len(),
+                actual: coeffs.len(),
+            });
+        }
+
+        let mut prepared_inputs = E::G1::zero();
+        for (public_inputs, coeff) in input_sets.iter().zip(coeffs) {
+            prepared_inputs += Self::prepare_inputs(pvk, public_inputs)? * coeff;
+        }
+
+        Ok(Self::verify_proof_with_prepared_inputs(
+            pvk,
+            proof,
+            &prepared_inputs,
+        )?)
+    }
+
+    /// Verify many proofs of the same circuit against the prepared verification key `pvk`
+    /// at once, where `proofs[i]` is checked against `public_inputs[i]`. The verification
+    /// equations are combined with random coefficients `r_i` drawn from `rng`:
+    ///
+    /// `prod_i e(r_i * A_i, B_i) * e(sum_i r_i * IC_i, -gamma) * e(sum_i r_i * C_i, -delta)
+    ///     == e(alpha, beta)^(sum_i r_i)`,
+    ///
+    /// which costs `n + 2` Miller loops, a single final exponentiation, and two MSMs. If any
+    /// proof is invalid, this returns `false` except with negligible probability. It does not
+    /// identify which proof is invalid. With the `parallel` feature, the Miller loops are
+    /// computed in parallel.
+    pub fn batch_verify_proofs(
+        pvk: &PreparedVerifyingKey<E>,
+        proofs: &[Proof<E>],
+        public_inputs: &[Vec<E::ScalarField>],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<bool> {
+        if proofs.len() != public_inputs.len() {
+            return Err(SynthesisError::AssignmentMissing);
+        }
+        if proofs.is_empty() {
+            return Ok(true);
+        }
+
+        let gamma_abc_g1 = &pvk.vk.gamma_abc_g1;
+        let mut input_scalars = vec![E::ScalarField::zero(); gamma_abc_g1.len()];
+        let mut coeffs = Vec::with_capacity(proofs.len());
+        let mut scaled_a = Vec::with_capacity(proofs.len());
+        let mut g2_elements = Vec::with_capacity(proofs.len() + 2);
+        for (proof, inputs) in proofs.iter().zip(public_inputs) {
+            if (inputs.len() + 1) != gamma_abc_g1.len() {
+                return Err(SynthesisError::MalformedVerifyingKey);
+            }
+
+            let r = E::ScalarField::rand(rng);
+            input_scalars[0] += r;
+            for (scalar, input) in input_scalars[1..].iter_mut().zip(inputs) {
+                *scalar += r * input;
+            }
+            scaled_a.push(proof.a * r);
+            g2_elements.push(E::G2Prepared::from(proof.b));
+            coeffs.push(r);
+        }
+
+        let mut g1_elements = Vec::with_capacity(proofs.len() + 2);
+        g1_elements.extend(
+            E::G1::normalize_batch(&scaled_a)
+                .into_iter()
+                .map(E::G1Prepared::from),
+        );
+
+        let input_scalars = input_scalars
+            .iter()
+            .map(|s| s.into_bigint())
+            .collect::<Vec<_>>();
+        let prepared_inputs = E::G1::msm_bigint(gamma_abc_g1, &input_scalars);
+
+        let c = proofs.iter().map(|proof| proof.c).collect::<Vec<_>>();
+        let c_coeffs = coeffs.iter().map(|r| r.into_bigint()).collect::<Vec<_>>();
+        let combined_c = E::G1::msm_bigint(&c, &c_coeffs);
+
+        g1_elements.push(prepared_inputs.into_affine().into());
+        g2_elements.push(pvk.gamma_g2_neg_pc.clone());
+        g1_elements.push(combined_c.into_affine().into());
+        g2_elements.push(pvk.delta_g2_neg_pc.clone());
+
+        // Split the Miller loops into one chunk per thread and multiply the partial
+        // results, so that only a single final exponentiation remains.
+        #[cfg(feature = "parallel")]
+        let num_chunks = rayon::current_num_threads();
+        #[cfg(not(feature = "parallel"))]
+        let num_chunks = 1;
+        let chunk_size = ark_std::cmp::max(1, (g1_elements.len() + num_chunks - 1) / num_chunks);
+        let qap = cfg_chunks!(g1_elements, chunk_size)
+            .zip(cfg_chunks!(g2_elements, chunk_size))
+            .map(|(g1, g2)| E::multi_miller_loop(g1.to_vec(), g2.to_vec()).0)
+            .product::<E::TargetField>();
+        let test = E::final_exponentiation(MillerLoopOutput(qap))
+            .ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        let coeff_sum = coeffs.iter().sum::<E::ScalarField>();
+        Ok(test.0 == pvk.alpha_g1_beta_g2.pow(coeff_sum.into_bigint()))
+    }
+
+    /// Verify proofs of possibly different circuits at once, where each entry holds a
+    /// prepared verification key, a proof, and its public inputs. Each verification
+    /// equation is scaled by a random coefficient `r_i` drawn from `rng`, applied to the
+    /// `G1` elements, and the products are checked against `prod_i e(alpha_i, beta_i)^r_i`.
+    /// This costs `3n` Miller loops but only a single final exponentiation. If any proof is
+    /// invalid, this returns `false` except with negligible probability.
+    pub fn batch_verify_mixed(
+        proofs_with_pvks: &[(PreparedVerifyingKey<E>, Proof<E>, Vec<E::ScalarField>)],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<bool> {
+        let mut g1_elements = Vec::with_capacity(3 * proofs_with_pvks.len());
+        let mut g2_elements = Vec::with_capacity(3 * proofs_with_pvks.len());
+        let mut expected = E::TargetField::one();
+        for (pvk, proof, public_inputs) in proofs_with_pvks {
+            let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
+            let r = E::ScalarField::rand(rng);
+
+            g1_elements.push(E::G1Prepared::from((proof.a * r).into_affine()));
+            g2_elements.push(E::G2Prepared::from(proof.b));
+            g1_elements.push((prepared_inputs * r).into_affine().into());
+            g2_elements.push(pvk.gamma_g2_neg_pc.clone());
+            g1_elements.push((proof.c * r).into_affine().into());
+            g2_elements.push(pvk.delta_g2_neg_pc.clone());
+
+            expected *= pvk.alpha_g1_beta_g2.pow(r.into_bigint());
+        }
+
+        let qap = E::multi_miller_loop(g1_elements, g2_elements);
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        Ok(test.0 == expected)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
+    /// with respect to public inputs given as little-endian `u64` limbs, as
+    /// commonly passed across FFI boundaries. Returns [`InputError::NonCanonical`]
+    /// if any input is not the canonical encoding of a scalar field element, i.e.
+    /// is not below the modulus.
+    pub fn verify_proof_from_limbs<const N: usize>(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        inputs_limbs: &[[u64; N]],
+    ) -> Result<bool, InputError> {
+        let public_inputs = inputs_limbs
+            .iter()
+            .enumerate()
+            .map(|(i, limbs)| {
+                scalar_from_limbs::<E::ScalarField, N>(limbs).ok_or(InputError::NonCanonical(i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::verify_proof(pvk, proof, &public_inputs)?)
+    }
+
+    /// Decompose the public inputs into limbs of `outer_field_bits` bits each,
+    /// represented as elements of the field `F` of an outer circuit that verifies
+    /// the proof, as in recursion over BLS12-377 and BW6-761. Each input is split
+    /// into `ceil(E::ScalarField::MODULUS_BIT_SIZE / outer_field_bits)` limbs,
+    /// least significant first, and the limbs of all inputs are concatenated.
+    ///
+    /// Panics unless `0 < outer_field_bits < F::MODULUS_BIT_SIZE`, so that every
+    /// limb fits in `F`.
+    pub fn public_inputs_as_field_elements<F: PrimeField>(
+        inputs: &[E::ScalarField],
+        outer_field_bits: usize,
+    ) -> Vec<F> {
+        assert!(outer_field_bits > 0 && outer_field_bits < F::MODULUS_BIT_SIZE as usize);
+        let input_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        inputs
+            .iter()
+            .flat_map(|input| {
+                let bits = input.into_bigint().to_bits_le();
+                bits[..input_bits]
+                    .chunks(outer_field_bits)
+                    .map(|limb| {
+                        F::from_bigint(F::BigInt::from_bits_le(limb))
+                            .expect("limb is smaller than the modulus")
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Recombine limbs produced by [`Self::public_inputs_as_field_elements`] into
+    /// the public inputs. Returns `None` if the number of limbs does not match,
+    /// if a limb exceeds `outer_field_bits` bits, or if the recombined value is
+    /// not a canonical element of `E::ScalarField`.
+    pub fn public_inputs_from_field_elements<F: PrimeField>(
+        limbs: &[F],
+        outer_field_bits: usize,
+    ) -> Option<Vec<E::ScalarField>> {
+        assert!(outer_field_bits > 0 && outer_field_bits < F::MODULUS_BIT_SIZE as usize);
+        let input_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        let limbs_per_input = (input_bits + outer_field_bits - 1) / outer_field_bits;
+        if limbs.len() % limbs_per_input != 0 {
+            return None;
+        }
+
+        limbs
+            .chunks(limbs_per_input)
+            .map(|input_limbs| {
+                let mut bits = Vec::with_capacity(limbs_per_input * outer_field_bits);
+                for limb in input_limbs {
+                    let limb_bits = limb.into_bigint().to_bits_le();
+                    if limb_bits[outer_field_bits..].iter().any(|b| *b) {
+                        return None;
+                    }
+                    bits.extend_from_slice(&limb_bits[..outer_field_bits]);
+                }
+                if bits[input_bits..].iter().any(|b| *b) {
+                    return None;
+                }
+                bits.truncate(input_bits);
+                E::ScalarField::from_bigint(<E::ScalarField as PrimeField>::BigInt::from_bits_le(
+                    &bits,
+                ))
+            })
+            .collect()
+    }
 }